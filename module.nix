@@ -5,6 +5,7 @@
 }:
 with lib; let
   cfg = config.services.palantir;
+  probeTargets = cfg.probes.icmp ++ cfg.probes.tcp ++ cfg.probes.http;
 in
 {
   options.services.palantir = {
@@ -24,6 +25,30 @@ in
       description = "enable zfs integration";
     };
 
+    smart = mkOption rec {
+      type = types.bool;
+      default = false;
+      example = true;
+      description = ''
+        enable smart monitoring using smartctl.
+
+        Warning: this runs the whole exporter with CAP_SYS_RAWIO, CAP_SYS_ADMIN and the disk group,
+        giving the network facing service raw access to all disks.
+      '';
+    };
+
+    lvm = mkOption rec {
+      type = types.bool;
+      default = false;
+      example = true;
+      description = ''
+        enable lvm volume group and thin pool monitoring.
+
+        Warning: this runs the whole exporter with CAP_SYS_ADMIN and the disk group,
+        giving the network facing service raw access to all disks.
+      '';
+    };
+
     docker = mkOption rec {
       type = types.bool;
       default = false;
@@ -38,6 +63,36 @@ in
       description = "enable mdns discovery";
     };
 
+    probes = {
+      icmp = mkOption {
+        type = types.listOf types.str;
+        default = [ ];
+        example = [ "192.168.1.1" ];
+        description = "hosts to ping";
+      };
+
+      tcp = mkOption {
+        type = types.listOf types.str;
+        default = [ ];
+        example = [ "nas.lan:445" ];
+        description = "host:port targets to connect to";
+      };
+
+      http = mkOption {
+        type = types.listOf types.str;
+        default = [ ];
+        example = [ "https://example.com/health" ];
+        description = "urls to request";
+      };
+
+      caFile = mkOption {
+        type = types.nullOr types.path;
+        default = null;
+        example = "/etc/ssl/certs/internal-ca.pem";
+        description = "pem bundle with additional ca certificates to trust for https probes";
+      };
+    };
+
     openPort = mkOption rec {
       type = types.bool;
       default = false;
@@ -76,7 +131,7 @@ in
     systemd.services."palantir" = {
       wantedBy = [ "multi-user.target" ];
       after = [ "systemd-networkd-wait-online.service" ];
-      path = lib.optional cfg.zfs pkgs.zfs;
+      path = lib.optional cfg.zfs pkgs.zfs ++ lib.optional cfg.smart pkgs.smartmontools ++ lib.optional cfg.lvm pkgs.lvm2;
       environment =
         {
           PORT = toString cfg.port;
@@ -89,6 +144,36 @@ in
             DISABLE_MDNS = "true";
           }
           else { }
+        )
+        // (
+          if cfg.smart
+          then {
+            ENABLE_SMART = "true";
+          }
+          else { }
+        )
+        // (
+          if cfg.lvm
+          then {
+            ENABLE_LVM = "true";
+          }
+          else { }
+        )
+        // (
+          if (probeTargets != [ ])
+          then {
+            PROBE_ICMP = concatStringsSep "," cfg.probes.icmp;
+            PROBE_TCP = concatStringsSep "," cfg.probes.tcp;
+            PROBE_HTTP = concatStringsSep "," cfg.probes.http;
+          }
+          else { }
+        )
+        // (
+          if (cfg.probes.caFile != null)
+          then {
+            PROBE_CA_FILE = toString cfg.probes.caFile;
+          }
+          else { }
         );
 
       serviceConfig = {
@@ -100,7 +185,7 @@ in
         ProtectHome = true;
         NoNewPrivileges = true;
         ProtectClock = !cfg.zfs; # Enabling this breaks libzfs
-        CapabilityBoundingSet = true;
+        CapabilityBoundingSet = if (cfg.smart || cfg.lvm) then lib.optional cfg.smart "CAP_SYS_RAWIO" ++ [ "CAP_SYS_ADMIN" ] else true;
         ProtectKernelLogs = true;
         ProtectControlGroups = true;
         SystemCallArchitectures = "native";
@@ -113,9 +198,11 @@ in
         RestrictAddressFamilies = [ "AF_INET" "AF_INET6" "AF_NETLINK" ] ++ lib.optional cfg.docker "AF_UNIX"; # netlink is required to make `getifaddrs` not err
         RestrictRealtime = true;
         SystemCallFilter = [ "@system-service" "~@resources" "~@privileged" ];
-        IPAddressAllow = [ "localhost" ] ++ lib.optional cfg.mdns "multicast";
+        IPAddressAllow = [ "localhost" ] ++ lib.optional cfg.mdns "multicast" ++ lib.optional (probeTargets != [ ]) "any";
         UMask = "0077";
-        SupplementaryGroups = [ "powermonitoring" ] ++ lib.optional cfg.docker "docker";
+        SupplementaryGroups = [ "powermonitoring" ] ++ lib.optional cfg.docker "docker" ++ lib.optional (cfg.smart || cfg.lvm) "disk";
+        # smartctl needs raw io access for ata pass-through and admin access for nvme, the lvm tools need admin access to query device-mapper
+        AmbientCapabilities = lib.optional cfg.smart "CAP_SYS_RAWIO" ++ lib.optional (cfg.smart || cfg.lvm) "CAP_SYS_ADMIN";
       };
     };
   };
@@ -1,4 +1,4 @@
-use crate::SensorData;
+use crate::{escape_label, SensorData};
 use std::array::IntoIter;
 use std::borrow::Cow;
 use std::fmt::Write;
@@ -38,6 +38,8 @@ pub struct Memory {
     pub total: u64,
     pub free: u64,
     pub available: u64,
+    /// Any additional memory statistics, as metric name suffix and value
+    pub details: Vec<(String, u64)>,
 }
 
 impl SensorData for Memory {
@@ -55,6 +57,9 @@ impl SensorData for Memory {
         )
         .ok();
         writeln!(&mut w, "memory_free{{host=\"{}\"}} {}", hostname, self.free).ok();
+        for (name, value) in &self.details {
+            writeln!(&mut w, "memory_{}{{host=\"{}\"}} {}", name, hostname, value).ok();
+        }
     }
 }
 
@@ -94,6 +99,20 @@ pub struct NetStats {
     pub interface: String,
     pub bytes_sent: u64,
     pub bytes_received: u64,
+    pub packets_sent: u64,
+    pub packets_received: u64,
+    pub errors_sent: u64,
+    pub errors_received: u64,
+    pub drops_sent: u64,
+    pub drops_received: u64,
+    pub fifo_errors_sent: u64,
+    pub fifo_errors_received: u64,
+    pub frame_errors_received: u64,
+    pub compressed_sent: u64,
+    pub compressed_received: u64,
+    pub multicast_received: u64,
+    pub collisions: u64,
+    pub carrier_errors: u64,
 }
 
 impl SensorData for NetStats {
@@ -111,6 +130,30 @@ impl SensorData for NetStats {
                 hostname, self.interface, self.bytes_received
             )
             .ok();
+            let counters = [
+                ("packets_sent", self.packets_sent),
+                ("packets_received", self.packets_received),
+                ("errors_sent", self.errors_sent),
+                ("errors_received", self.errors_received),
+                ("drops_sent", self.drops_sent),
+                ("drops_received", self.drops_received),
+                ("fifo_errors_sent", self.fifo_errors_sent),
+                ("fifo_errors_received", self.fifo_errors_received),
+                ("frame_errors_received", self.frame_errors_received),
+                ("compressed_sent", self.compressed_sent),
+                ("compressed_received", self.compressed_received),
+                ("multicast_received", self.multicast_received),
+                ("collisions", self.collisions),
+                ("carrier_errors", self.carrier_errors),
+            ];
+            for (name, value) in counters {
+                writeln!(
+                    &mut w,
+                    "net_{}{{host=\"{}\", network=\"{}\"}} {}",
+                    name, hostname, self.interface, value
+                )
+                .ok();
+            }
         }
     }
 }
@@ -134,51 +177,135 @@ impl GpuUsage {
 #[derive(Debug, Clone, Default)]
 pub struct DiskStats {
     pub interface: String,
+    /// Friendly name for the device, like the device-mapper name or the stable `/dev/disk/by-id` name
+    pub alias: Option<String>,
     pub bytes_sent: u64,
     pub bytes_received: u64,
+    pub reads: u64,
+    pub reads_merged: u64,
+    /// Time spent reading in milliseconds
+    pub read_time: u64,
+    pub writes: u64,
+    pub writes_merged: u64,
+    /// Time spent writing in milliseconds
+    pub write_time: u64,
+    pub in_flight: u64,
+    /// Time spent doing io in milliseconds
+    pub io_time: u64,
+    /// Time spent doing io in milliseconds, weighted by the number of in flight requests
+    pub weighted_io_time: u64,
+    pub discards: u64,
+    pub discards_merged: u64,
+    pub bytes_discarded: u64,
+    /// Time spent discarding in milliseconds
+    pub discard_time: u64,
+    pub flushes: u64,
+    /// Time spent flushing in milliseconds
+    pub flush_time: u64,
 }
 
 impl SensorData for DiskStats {
     fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        let labels = match &self.alias {
+            Some(alias) => format!(
+                "disk=\"{}\", alias=\"{}\"",
+                self.interface,
+                escape_label(alias)
+            ),
+            None => format!("disk=\"{}\"", self.interface),
+        };
         if self.bytes_received > 0 || self.bytes_sent > 0 {
             writeln!(
                 &mut w,
-                "disk_sent{{host=\"{}\", disk=\"{}\"}} {}",
-                hostname, self.interface, self.bytes_sent
+                "disk_sent{{host=\"{}\", {}}} {}",
+                hostname, labels, self.bytes_sent
             )
             .ok();
             writeln!(
                 &mut w,
-                "disk_received{{host=\"{}\", disk=\"{}\"}} {}",
-                hostname, self.interface, self.bytes_received
+                "disk_received{{host=\"{}\", {}}} {}",
+                hostname, labels, self.bytes_received
+            )
+            .ok();
+            let counters = [
+                ("reads", self.reads),
+                ("reads_merged", self.reads_merged),
+                ("read_time", self.read_time),
+                ("writes", self.writes),
+                ("writes_merged", self.writes_merged),
+                ("write_time", self.write_time),
+                ("in_flight", self.in_flight),
+                ("io_time", self.io_time),
+                ("weighted_io_time", self.weighted_io_time),
+                ("discards", self.discards),
+                ("discards_merged", self.discards_merged),
+                ("discarded", self.bytes_discarded),
+                ("discard_time", self.discard_time),
+                ("flushes", self.flushes),
+                ("flush_time", self.flush_time),
+            ];
+            for (name, value) in counters {
+                writeln!(
+                    &mut w,
+                    "disk_{}{{host=\"{}\", {}}} {}",
+                    name, hostname, labels, value
+                )
+                .ok();
+            }
+        }
+        if let Some(alias) = &self.alias {
+            writeln!(
+                &mut w,
+                "disk_alias{{host=\"{}\", disk=\"{}\", alias=\"{}\"}} 1",
+                hostname,
+                self.interface,
+                escape_label(alias)
             )
             .ok();
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct DiskUsage {
     pub name: String,
+    pub device: String,
+    pub fstype: String,
     pub size: u64,
     pub free: u64,
+    /// Space only available to the root user, not reported for sources that don't know it like zfs pools
+    pub reserved: Option<u64>,
+    pub inodes: u64,
+    pub inodes_free: u64,
+    pub readonly: Option<bool>,
 }
 
 impl SensorData for DiskUsage {
     fn write<W: Write>(&self, mut w: W, hostname: &str) {
         if self.size > 0 {
-            writeln!(
-                &mut w,
-                "disk_size{{host=\"{}\", disk=\"{}\"}} {}",
-                hostname, self.name, self.size
-            )
-            .ok();
-            writeln!(
-                &mut w,
-                "disk_free{{host=\"{}\", disk=\"{}\"}} {}",
-                hostname, self.name, self.free
-            )
-            .ok();
+            let values = [
+                ("size", Some(self.size)),
+                ("free", Some(self.free)),
+                ("reserved", self.reserved),
+                ("readonly", self.readonly.map(u64::from)),
+            ];
+            // some filesystems like btrfs and zfs don't have a fixed number of inodes
+            let inodes = [
+                ("inodes", Some(self.inodes)),
+                ("inodes_free", Some(self.inodes_free)),
+            ];
+            let inodes = if self.inodes > 0 { &inodes[..] } else { &[] };
+            for &(name, value) in values.iter().chain(inodes) {
+                let Some(value) = value else {
+                    continue;
+                };
+                writeln!(
+                    &mut w,
+                    "disk_{}{{host=\"{}\", disk=\"{}\", device=\"{}\", fstype=\"{}\"}} {}",
+                    name, hostname, self.name, self.device, self.fstype, value
+                )
+                .ok();
+            }
         }
     }
 }
@@ -1,5 +1,6 @@
 #[cfg(not(target_os = "windows"))]
 use procfs::ProcError;
+use std::borrow::Cow;
 use std::ffi::NulError;
 use std::fmt::Write;
 use std::num::{ParseFloatError, ParseIntError};
@@ -40,6 +41,8 @@ pub enum Error {
     #[cfg(not(target_os = "windows"))]
     #[error(transparent)]
     Proc(#[from] ProcError),
+    #[error(transparent)]
+    InvalidRegex(#[from] regex::Error),
     #[error("Failed to query vfs stats")]
     StatVfs,
     #[cfg(target_os = "windows")]
@@ -96,6 +99,23 @@ pub fn hostname() -> Result<String> {
         .map_err(|_| Error::InvalidHostName)
 }
 
+/// Escape a label value for the prometheus text format, which only defines escapes for `\\`, `\"` and `\n`
+pub fn escape_label(value: &str) -> Cow<'_, str> {
+    if !value.contains(['\\', '"', '\n']) {
+        return Cow::Borrowed(value);
+    }
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\\"),
+            '"' => escaped.push_str(r#"\""#),
+            '\n' => escaped.push_str(r"\n"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
 pub trait IoResultExt<T> {
     fn context(self, context: &'static str) -> Result<T, Error>;
 }
@@ -105,3 +125,15 @@ impl<T> IoResultExt<T> for Result<T, std::io::Error> {
         self.map_err(|e| Error::io(context, e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::escape_label;
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("Bob's Wi-Fi\t"), "Bob's Wi-Fi\t");
+        assert_eq!(escape_label(r#"a\b"c"#), r#"a\\b\"c"#);
+        assert_eq!(escape_label("a\nb"), r"a\nb");
+    }
+}
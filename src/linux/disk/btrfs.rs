@@ -0,0 +1,283 @@
+use crate::linux::hwmon::{read_num, read_trimmed};
+use crate::{escape_label, SensorData};
+use std::fmt::Write;
+use std::fs::{canonicalize, read_dir, read_to_string};
+use std::path::Path;
+
+const ALLOCATION_TYPES: &[&str] = &["data", "metadata", "system"];
+
+#[derive(Debug, Default)]
+pub struct BtrfsAllocation {
+    /// One of "data", "metadata" or "system"
+    kind: String,
+    /// Space allocated for chunks
+    size: u64,
+    used: u64,
+    /// Raw space on the disks used by the allocated chunks, including the redundancy of the profile
+    disk_size: u64,
+    disk_used: u64,
+    /// Allocation per profile like "single", "dup" or "raid1"
+    profiles: Vec<(String, u64, u64)>,
+}
+
+#[derive(Debug, Default)]
+pub struct BtrfsDevice {
+    devid: String,
+    /// Error counters like "write" or "corruption"
+    errors: Vec<(String, u64)>,
+}
+
+#[derive(Debug, Default)]
+pub struct Btrfs {
+    uuid: String,
+    label: String,
+    mount_point: String,
+    allocations: Vec<BtrfsAllocation>,
+    devices: Vec<BtrfsDevice>,
+}
+
+impl SensorData for Btrfs {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        let label = escape_label(&self.label);
+        let mount_point = escape_label(&self.mount_point);
+        for allocation in &self.allocations {
+            let values = [
+                ("size", allocation.size),
+                ("used", allocation.used),
+                ("disk_size", allocation.disk_size),
+                ("disk_used", allocation.disk_used),
+            ];
+            for (name, value) in values {
+                writeln!(
+                    &mut w,
+                    r#"btrfs_allocation_{}{{host="{}", uuid="{}", label="{}", mount_point="{}", type="{}"}} {}"#,
+                    name, hostname, self.uuid, label, mount_point, allocation.kind, value
+                )
+                .ok();
+            }
+            for (profile, size, used) in &allocation.profiles {
+                writeln!(
+                    &mut w,
+                    r#"btrfs_allocation_profile_size{{host="{}", uuid="{}", label="{}", mount_point="{}", type="{}", profile="{}"}} {}"#,
+                    hostname, self.uuid, label, mount_point, allocation.kind, escape_label(profile), size
+                )
+                .ok();
+                writeln!(
+                    &mut w,
+                    r#"btrfs_allocation_profile_used{{host="{}", uuid="{}", label="{}", mount_point="{}", type="{}", profile="{}"}} {}"#,
+                    hostname, self.uuid, label, mount_point, allocation.kind, escape_label(profile), used
+                )
+                .ok();
+            }
+        }
+        for device in &self.devices {
+            for (kind, count) in &device.errors {
+                writeln!(
+                    &mut w,
+                    r#"btrfs_device_errors{{host="{}", uuid="{}", label="{}", mount_point="{}", devid="{}", type="{}"}} {}"#,
+                    hostname, self.uuid, label, mount_point, escape_label(&device.devid), escape_label(kind), count
+                )
+                .ok();
+            }
+        }
+    }
+}
+
+/// Allocation and device error statistics for all mounted btrfs filesystems from `/sys/fs/btrfs`
+pub fn btrfs_filesystems() -> Vec<Btrfs> {
+    let mounts = read_to_string("/proc/mounts").unwrap_or_default();
+    read_filesystems(Path::new("/sys/fs/btrfs"), &mounts)
+}
+
+fn read_filesystems(root: &Path, mounts: &str) -> Vec<Btrfs> {
+    let mut filesystems: Vec<Btrfs> = read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            // the directory also contains the "features" directory
+            if !path.join("allocation").is_dir() {
+                return None;
+            }
+            Some(Btrfs {
+                uuid: entry.file_name().into_string().ok()?,
+                label: read_trimmed(path.join("label")).unwrap_or_default(),
+                mount_point: mount_point(&path, mounts).unwrap_or_default(),
+                allocations: ALLOCATION_TYPES
+                    .iter()
+                    .map(|kind| allocation(kind, &path.join("allocation").join(kind)))
+                    .collect(),
+                devices: devices(&path),
+            })
+        })
+        .collect();
+    filesystems.sort_by(|a, b| a.uuid.cmp(&b.uuid));
+    filesystems
+}
+
+fn allocation(kind: &str, path: &Path) -> BtrfsAllocation {
+    let mut profiles: Vec<(String, u64, u64)> = read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let profile = entry.file_name().into_string().ok()?;
+            let path = entry.path();
+            Some((
+                profile,
+                read_num(path.join("total_bytes")).unwrap_or_default(),
+                read_num(path.join("used_bytes")).unwrap_or_default(),
+            ))
+        })
+        .collect();
+    profiles.sort();
+
+    BtrfsAllocation {
+        kind: kind.into(),
+        size: read_num(path.join("total_bytes")).unwrap_or_default(),
+        used: read_num(path.join("bytes_used")).unwrap_or_default(),
+        disk_size: read_num(path.join("disk_total")).unwrap_or_default(),
+        disk_used: read_num(path.join("disk_used")).unwrap_or_default(),
+        profiles,
+    }
+}
+
+/// Error counters for each device, `devinfo/<devid>/error_stats` is in the format
+///
+/// ```text
+/// write_errs 0
+/// read_errs 0
+/// flush_errs 0
+/// corruption_errs 0
+/// generation_errs 0
+/// ```
+fn devices(path: &Path) -> Vec<BtrfsDevice> {
+    let mut devices: Vec<BtrfsDevice> = read_dir(path.join("devinfo"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let stats = read_to_string(entry.path().join("error_stats")).ok()?;
+            Some(BtrfsDevice {
+                devid: entry.file_name().into_string().ok()?,
+                errors: parse_error_stats(&stats),
+            })
+        })
+        .collect();
+    devices.sort_by(|a, b| a.devid.cmp(&b.devid));
+    devices
+}
+
+fn parse_error_stats(stats: &str) -> Vec<(String, u64)> {
+    stats
+        .lines()
+        .filter_map(|line| {
+            let (kind, count) = line.split_once(' ')?;
+            let kind = kind.trim_end_matches("_errs");
+            Some((kind.to_string(), count.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Find the first mount point of the filesystem by matching the mounted device against the devices of the filesystem
+///
+/// The device in `/proc/mounts` can be a symlink like `/dev/mapper/root`, while `devices` lists the block device names.
+fn mount_point(path: &Path, mounts: &str) -> Option<String> {
+    let devices = path.join("devices");
+    mounts.lines().find_map(|line| {
+        let mut parts = line.split_ascii_whitespace();
+        let (device, mount_point, fstype) = (parts.next()?, parts.next()?, parts.next()?);
+        if fstype != "btrfs" {
+            return None;
+        }
+        let device = canonicalize(device).ok()?;
+        let name = device.file_name()?;
+        devices.join(name).exists().then(|| mount_point.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Vec<Btrfs> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/btrfs");
+        read_filesystems(&root, "")
+    }
+
+    #[test]
+    fn test_read_allocation() {
+        let filesystems = fixture();
+        // the "features" directory isn't a filesystem
+        assert_eq!(filesystems.len(), 1);
+        let btrfs = &filesystems[0];
+        assert_eq!(btrfs.uuid, "4a2bf6b1-7e0c-4d7e-9b3f-2f6c8d1e5a90");
+        assert_eq!(btrfs.label, r#"backup "2024""#);
+        assert_eq!(btrfs.mount_point, "");
+
+        let kinds = btrfs
+            .allocations
+            .iter()
+            .map(|allocation| allocation.kind.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, ALLOCATION_TYPES);
+        let data = &btrfs.allocations[0];
+        assert_eq!(data.size, 10737418240);
+        assert_eq!(data.used, 5368709120);
+        assert_eq!(data.disk_size, 21474836480);
+        assert_eq!(data.disk_used, 10737418240);
+        assert_eq!(
+            data.profiles,
+            vec![("raid1".to_string(), 10737418240, 5368709120)]
+        );
+        let metadata = &btrfs.allocations[1];
+        assert_eq!(metadata.disk_used, 536870912);
+        assert_eq!(
+            metadata.profiles,
+            vec![("dup".to_string(), 1073741824, 268435456)]
+        );
+    }
+
+    #[test]
+    fn test_read_device_errors() {
+        let filesystems = fixture();
+        let devices = &filesystems[0].devices;
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].devid, "1");
+        assert!(devices[0].errors.iter().all(|(_, count)| *count == 0));
+        assert_eq!(devices[1].devid, "2");
+        assert_eq!(
+            devices[1].errors,
+            vec![
+                ("write".to_string(), 3),
+                ("read".to_string(), 0),
+                ("flush".to_string(), 0),
+                ("corruption".to_string(), 1),
+                ("generation".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_error_stats_invalid() {
+        assert_eq!(
+            parse_error_stats("write_errs 2\ngarbage\nread_errs x\n"),
+            vec![("write".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_write_escapes_labels() {
+        let filesystems = fixture();
+        let mut out = String::new();
+        filesystems[0].write(&mut out, "host");
+        assert!(out.contains(
+            r#"btrfs_allocation_used{host="host", uuid="4a2bf6b1-7e0c-4d7e-9b3f-2f6c8d1e5a90", label="backup \"2024\"", mount_point="", type="data"} 5368709120"#
+        ));
+        assert!(out.contains(
+            r#"btrfs_device_errors{host="host", uuid="4a2bf6b1-7e0c-4d7e-9b3f-2f6c8d1e5a90", label="backup \"2024\"", mount_point="", devid="2", type="corruption"} 1"#
+        ));
+    }
+}
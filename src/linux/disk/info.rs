@@ -0,0 +1,125 @@
+use crate::linux::disk::DiskFilter;
+use crate::linux::hwmon::{read_num, read_trimmed};
+use crate::{escape_label, MultiSensorSource, Result, SensorData};
+use std::fmt::Write;
+use std::fs::{read, read_dir};
+use std::path::Path;
+use std::vec::IntoIter;
+
+/// Read the first file that exists, since the attribute names differ between device types
+fn read_first(path: &Path, names: &[&str]) -> String {
+    names
+        .iter()
+        .find_map(|name| read_trimmed(path.join(name)))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Default)]
+pub struct DiskInfo {
+    disk: String,
+    model: String,
+    serial: String,
+    vendor: String,
+    firmware: String,
+    rotational: bool,
+    logical_block_size: u64,
+    physical_block_size: u64,
+    scheduler: String,
+    /// Size in bytes
+    size: u64,
+}
+
+impl SensorData for DiskInfo {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"disk_info{{host="{}", disk="{}", model="{}", serial="{}", vendor="{}", firmware="{}", rotational="{}", logical_block_size="{}", physical_block_size="{}", scheduler="{}"}} 1"#,
+            hostname,
+            self.disk,
+            escape_label(&self.model),
+            escape_label(&self.serial),
+            escape_label(&self.vendor),
+            escape_label(&self.firmware),
+            self.rotational as u8,
+            self.logical_block_size,
+            self.physical_block_size,
+            self.scheduler
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"disk_capacity{{host="{}", disk="{}"}} {}"#,
+            hostname, self.disk, self.size
+        )
+        .ok();
+    }
+}
+
+pub struct DiskInfoSource {
+    filter: DiskFilter,
+}
+
+impl DiskInfoSource {
+    pub fn new(filter: DiskFilter) -> DiskInfoSource {
+        DiskInfoSource { filter }
+    }
+}
+
+impl MultiSensorSource for DiskInfoSource {
+    type Data = DiskInfo;
+    type Iter<'a> = IntoIter<Result<DiskInfo>>;
+
+    fn read(&mut self) -> Result<Self::Iter<'_>> {
+        Ok(read_dir("/sys/block")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let disk = entry.file_name().into_string().ok()?;
+                self.filter
+                    .matches(&disk)
+                    .then(|| Ok(disk_info(disk, &entry.path())))
+            })
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+}
+
+fn disk_info(disk: String, path: &Path) -> DiskInfo {
+    let device = path.join("device");
+    let queue = path.join("queue");
+    // the active scheduler is marked with brackets: "none [mq-deadline] kyber bfq"
+    let scheduler = read_trimmed(queue.join("scheduler"))
+        .and_then(|schedulers| {
+            let (_, active) = schedulers.split_once('[')?;
+            Some(active.split_once(']')?.0.to_string())
+        })
+        .unwrap_or_default();
+    let serial = read_trimmed(path.join("serial"))
+        .or_else(|| read_trimmed(device.join("serial")))
+        .or_else(|| scsi_serial(&device))
+        .unwrap_or_default();
+
+    DiskInfo {
+        model: read_first(&device, &["model", "name"]),
+        serial,
+        vendor: read_first(&device, &["vendor"]),
+        firmware: read_first(&device, &["firmware_rev", "rev", "fwrev"]),
+        rotational: read_num::<u8>(queue.join("rotational")) == Some(1),
+        logical_block_size: read_num(queue.join("logical_block_size")).unwrap_or_default(),
+        physical_block_size: read_num(queue.join("physical_block_size")).unwrap_or_default(),
+        scheduler,
+        // the size is always in 512 byte sectors, regardless of the block size
+        size: read_num::<u64>(path.join("size")).unwrap_or_default() * 512,
+        disk,
+    }
+}
+
+/// Scsi and sata disks only expose the serial number through the "unit serial number" vpd page
+fn scsi_serial(device: &Path) -> Option<String> {
+    let page = read(device.join("vpd_pg80")).ok()?;
+    let len = *page.get(3)? as usize;
+    let serial = String::from_utf8_lossy(page.get(4..4 + len)?);
+    let serial = serial.trim();
+    (!serial.is_empty()).then(|| serial.to_string())
+}
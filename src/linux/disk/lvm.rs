@@ -0,0 +1,369 @@
+use crate::SensorData;
+use serde::Deserialize;
+use std::fmt::Write;
+use std::io::ErrorKind;
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::Duration;
+use tracing::warn;
+
+static LVM_DATA: Mutex<Vec<Lvm>> = Mutex::new(Vec::new());
+
+/// `{"report": [{"vg": [...]}]}` or `{"report": [{"lv": [...]}]}`
+#[derive(Debug, Deserialize)]
+struct LvmReport {
+    report: Vec<LvmReportItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LvmReportItem {
+    #[serde(default)]
+    vg: Vec<VgReport>,
+    #[serde(default)]
+    lv: Vec<LvReport>,
+}
+
+/// All values are reported as strings, sizes are requested in bytes without suffix
+#[derive(Debug, Deserialize)]
+struct VgReport {
+    vg_name: String,
+    vg_size: String,
+    vg_free: String,
+}
+
+/// Percentages are empty when they don't apply to the volume type
+#[derive(Debug, Deserialize)]
+struct LvReport {
+    vg_name: String,
+    lv_name: String,
+    lv_size: String,
+    #[serde(default)]
+    segtype: String,
+    #[serde(default)]
+    origin: String,
+    #[serde(default)]
+    data_percent: String,
+    #[serde(default)]
+    metadata_percent: String,
+    #[serde(default)]
+    snap_percent: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Lvm {
+    VolumeGroup {
+        name: String,
+        size: u64,
+        free: u64,
+    },
+    LogicalVolume {
+        vg: String,
+        name: String,
+        segment_type: String,
+        size: u64,
+        /// Data usage of thin pools and thin volumes in percent
+        data_percent: Option<f64>,
+        /// Metadata usage of thin pools in percent
+        metadata_percent: Option<f64>,
+        origin: String,
+        /// Usage of the copy-on-write space of snapshots in percent
+        snapshot_percent: Option<f64>,
+    },
+}
+
+impl SensorData for Lvm {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        match self {
+            Lvm::VolumeGroup { name, size, free } => {
+                writeln!(
+                    &mut w,
+                    r#"lvm_vg_size{{host="{}", vg="{}"}} {}"#,
+                    hostname, name, size
+                )
+                .ok();
+                writeln!(
+                    &mut w,
+                    r#"lvm_vg_free{{host="{}", vg="{}"}} {}"#,
+                    hostname, name, free
+                )
+                .ok();
+            }
+            Lvm::LogicalVolume {
+                vg,
+                name,
+                segment_type,
+                size,
+                data_percent,
+                metadata_percent,
+                origin,
+                snapshot_percent,
+            } => {
+                writeln!(
+                    &mut w,
+                    r#"lvm_lv_size{{host="{}", vg="{}", lv="{}", type="{}"}} {}"#,
+                    hostname, vg, name, segment_type, size
+                )
+                .ok();
+                if let Some(percent) = data_percent {
+                    writeln!(
+                        &mut w,
+                        r#"lvm_lv_data_percent{{host="{}", vg="{}", lv="{}", type="{}"}} {}"#,
+                        hostname, vg, name, segment_type, percent
+                    )
+                    .ok();
+                }
+                if let Some(percent) = metadata_percent {
+                    writeln!(
+                        &mut w,
+                        r#"lvm_lv_metadata_percent{{host="{}", vg="{}", lv="{}", type="{}"}} {}"#,
+                        hostname, vg, name, segment_type, percent
+                    )
+                    .ok();
+                }
+                if let Some(percent) = snapshot_percent {
+                    writeln!(
+                        &mut w,
+                        r#"lvm_snapshot_percent{{host="{}", vg="{}", lv="{}", origin="{}"}} {}"#,
+                        hostname, vg, name, origin, percent
+                    )
+                    .ok();
+                }
+            }
+        }
+    }
+}
+
+fn parse_percent(percent: &str) -> Option<f64> {
+    percent.trim().parse().ok()
+}
+
+fn parse_vgs(output: &[u8]) -> serde_json::Result<Vec<Lvm>> {
+    let report: LvmReport = serde_json::from_slice(output)?;
+    Ok(report
+        .report
+        .into_iter()
+        .flat_map(|item| item.vg)
+        .map(|vg| Lvm::VolumeGroup {
+            size: vg.vg_size.parse().unwrap_or_default(),
+            free: vg.vg_free.parse().unwrap_or_default(),
+            name: vg.vg_name,
+        })
+        .collect())
+}
+
+fn parse_lvs(output: &[u8]) -> serde_json::Result<Vec<Lvm>> {
+    let report: LvmReport = serde_json::from_slice(output)?;
+    Ok(report
+        .report
+        .into_iter()
+        .flat_map(|item| item.lv)
+        .map(|lv| {
+            // thin snapshots have no copy-on-write space and report their usage as data percentage
+            let snapshot_percent = if lv.origin.is_empty() {
+                None
+            } else {
+                parse_percent(&lv.snap_percent)
+            };
+            Lvm::LogicalVolume {
+                size: lv.lv_size.parse().unwrap_or_default(),
+                data_percent: parse_percent(&lv.data_percent),
+                metadata_percent: parse_percent(&lv.metadata_percent),
+                snapshot_percent,
+                vg: lv.vg_name,
+                name: lv.lv_name,
+                segment_type: lv.segtype,
+                origin: lv.origin,
+            }
+        })
+        .collect())
+}
+
+fn lvm_command(command: &str, fields: &str) -> Result<Vec<u8>, String> {
+    // read-only mode doesn't take any locks, so it doesn't need write access to the lock directory
+    let out = Command::new(command)
+        .args([
+            "--readonly",
+            "--reportformat",
+            "json",
+            "--units",
+            "b",
+            "--nosuffix",
+            "-o",
+        ])
+        .arg(fields)
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => format!("{command} not found"),
+            _ => e.to_string(),
+        })?;
+    if out.status.success() {
+        Ok(out.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&out.stderr).trim().to_string())
+    }
+}
+
+fn read_lvm() -> Result<Vec<Lvm>, String> {
+    let vgs = lvm_command("vgs", "vg_name,vg_size,vg_free")?;
+    let lvs = lvm_command(
+        "lvs",
+        "vg_name,lv_name,lv_size,segtype,origin,data_percent,metadata_percent,snap_percent",
+    )?;
+    let mut result = parse_vgs(&vgs).map_err(|e| e.to_string())?;
+    result.extend(parse_lvs(&lvs).map_err(|e| e.to_string())?);
+    Ok(result)
+}
+
+/// Run `vgs` and `lvs` with the interval configured by `LVM_INTERVAL` in seconds (default 60)
+///
+/// When the lvm tools aren't installed or fail, a warning is logged and the collector stops.
+pub fn update_lvm() {
+    let interval = dotenvy::var("LVM_INTERVAL")
+        .ok()
+        .and_then(|interval| interval.parse().ok())
+        .unwrap_or(60);
+    loop {
+        match read_lvm() {
+            Ok(data) => *LVM_DATA.lock().unwrap() = data,
+            Err(error) => {
+                warn!(error, "Failed to read lvm status, disabling lvm monitoring");
+                LVM_DATA.lock().unwrap().clear();
+                return;
+            }
+        }
+        sleep(Duration::from_secs(interval));
+    }
+}
+
+/// The last read volume groups and logical volumes
+pub fn lvm_data() -> Vec<Lvm> {
+    LVM_DATA.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VGS: &str = include_str!("../../../tests/fixtures/lvm/vgs.json");
+    const LVS: &str = include_str!("../../../tests/fixtures/lvm/lvs.json");
+
+    fn find<'a>(volumes: &'a [Lvm], lv: &str) -> &'a Lvm {
+        volumes
+            .iter()
+            .find(|volume| matches!(volume, Lvm::LogicalVolume { name, .. } if name == lv))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_vgs() {
+        let groups = parse_vgs(VGS.as_bytes()).unwrap();
+        assert_eq!(groups.len(), 2);
+        let Lvm::VolumeGroup { name, size, free } = &groups[0] else {
+            panic!("expected a volume group");
+        };
+        assert_eq!(name, "data");
+        assert_eq!(*size, 3999956729856);
+        assert_eq!(*free, 1073741824);
+    }
+
+    #[test]
+    fn test_parse_plain_lv() {
+        let volumes = parse_lvs(LVS.as_bytes()).unwrap();
+        let Lvm::LogicalVolume {
+            vg,
+            size,
+            segment_type,
+            data_percent,
+            metadata_percent,
+            snapshot_percent,
+            ..
+        } = find(&volumes, "root")
+        else {
+            panic!("expected a logical volume");
+        };
+        assert_eq!(vg, "system");
+        assert_eq!(*size, 107374182400);
+        assert_eq!(segment_type, "linear");
+        assert_eq!(*data_percent, None);
+        assert_eq!(*metadata_percent, None);
+        assert_eq!(*snapshot_percent, None);
+    }
+
+    #[test]
+    fn test_parse_thin_pool() {
+        let volumes = parse_lvs(LVS.as_bytes()).unwrap();
+        let Lvm::LogicalVolume {
+            segment_type,
+            data_percent,
+            metadata_percent,
+            snapshot_percent,
+            ..
+        } = find(&volumes, "pool")
+        else {
+            panic!("expected a logical volume");
+        };
+        assert_eq!(segment_type, "thin-pool");
+        assert_eq!(*data_percent, Some(87.12));
+        assert_eq!(*metadata_percent, Some(12.4));
+        assert_eq!(*snapshot_percent, None);
+    }
+
+    #[test]
+    fn test_parse_thin_snapshot() {
+        let volumes = parse_lvs(LVS.as_bytes()).unwrap();
+        let Lvm::LogicalVolume {
+            origin,
+            data_percent,
+            snapshot_percent,
+            ..
+        } = find(&volumes, "media-20250101")
+        else {
+            panic!("expected a logical volume");
+        };
+        assert_eq!(origin, "media");
+        assert_eq!(*data_percent, Some(91.02));
+        assert_eq!(*snapshot_percent, None);
+    }
+
+    #[test]
+    fn test_parse_classic_snapshot() {
+        let volumes = parse_lvs(LVS.as_bytes()).unwrap();
+        let Lvm::LogicalVolume {
+            origin,
+            snapshot_percent,
+            ..
+        } = find(&volumes, "home-snap")
+        else {
+            panic!("expected a logical volume");
+        };
+        assert_eq!(origin, "home");
+        assert_eq!(*snapshot_percent, Some(23.81));
+
+        // the origin of a snapshot has no usage of its own
+        let Lvm::LogicalVolume {
+            snapshot_percent, ..
+        } = find(&volumes, "home")
+        else {
+            panic!("expected a logical volume");
+        };
+        assert_eq!(*snapshot_percent, None);
+    }
+
+    #[test]
+    fn test_write() {
+        let volumes = parse_lvs(LVS.as_bytes()).unwrap();
+        let mut out = String::new();
+        for volume in &volumes {
+            volume.write(&mut out, "host");
+        }
+        assert!(out.contains(
+            r#"lvm_lv_metadata_percent{host="host", vg="data", lv="pool", type="thin-pool"} 12.4"#
+        ));
+        assert!(out.contains(
+            r#"lvm_snapshot_percent{host="host", vg="system", lv="home-snap", origin="home"} 23.81"#
+        ));
+        assert!(!out.contains(r#"lvm_snapshot_percent{host="host", vg="data""#));
+        assert!(!out.contains(r#"lvm_lv_data_percent{host="host", vg="system", lv="root""#));
+    }
+}
@@ -0,0 +1,254 @@
+use crate::linux::hwmon::{read_num, read_trimmed};
+use crate::SensorData;
+use std::fmt::Write;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct MdMember {
+    device: String,
+    /// Comma separated list of flags like "in_sync", "faulty", "spare" or "write_mostly"
+    state: String,
+}
+
+impl MdMember {
+    fn has_state(&self, state: &str) -> bool {
+        self.state.split(',').any(|flag| flag == state)
+    }
+
+    /// Most raid levels clear `in_sync` when marking a member as faulty, but not all of them do
+    fn is_active(&self) -> bool {
+        self.has_state("in_sync") && !self.has_state("faulty")
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MdArray {
+    array: String,
+    level: String,
+    state: String,
+    degraded: u64,
+    raid_disks: u64,
+    sync_action: String,
+    /// Fraction of the current resync, recovery, check or reshape that is completed
+    sync_progress: Option<f64>,
+    /// Sync speed in bytes per second
+    sync_speed: Option<u64>,
+    members: Vec<MdMember>,
+}
+
+impl SensorData for MdArray {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"md_info{{host="{}", array="{}", level="{}", state="{}", sync_action="{}"}} 1"#,
+            hostname, self.array, self.level, self.state, self.sync_action
+        )
+        .ok();
+        let count = |state: &str| {
+            self.members
+                .iter()
+                .filter(|member| member.has_state(state))
+                .count()
+        };
+        let disks = [
+            ("disks", self.raid_disks as usize),
+            (
+                "disks_active",
+                self.members
+                    .iter()
+                    .filter(|member| member.is_active())
+                    .count(),
+            ),
+            ("disks_failed", count("faulty")),
+            ("disks_spare", count("spare")),
+            ("degraded", self.degraded as usize),
+        ];
+        for (name, value) in disks {
+            writeln!(
+                &mut w,
+                r#"md_{}{{host="{}", array="{}"}} {}"#,
+                name, hostname, self.array, value
+            )
+            .ok();
+        }
+        if let Some(progress) = self.sync_progress {
+            writeln!(
+                &mut w,
+                r#"md_sync_progress{{host="{}", array="{}", action="{}"}} {:.4}"#,
+                hostname, self.array, self.sync_action, progress
+            )
+            .ok();
+        }
+        if let Some(speed) = self.sync_speed {
+            writeln!(
+                &mut w,
+                r#"md_sync_speed{{host="{}", array="{}", action="{}"}} {}"#,
+                hostname, self.array, self.sync_action, speed
+            )
+            .ok();
+        }
+        for member in &self.members {
+            writeln!(
+                &mut w,
+                r#"md_member_info{{host="{}", array="{}", member="{}", state="{}"}} 1"#,
+                hostname, self.array, member.device, member.state
+            )
+            .ok();
+            writeln!(
+                &mut w,
+                r#"md_member_in_sync{{host="{}", array="{}", member="{}"}} {}"#,
+                hostname,
+                self.array,
+                member.device,
+                member.is_active() as u8
+            )
+            .ok();
+        }
+    }
+}
+
+/// Status of all md raid arrays
+///
+/// The arrays are listed from `/proc/mdstat`, the details are read from `/sys/block/md*/md`.
+pub fn md_arrays() -> Vec<MdArray> {
+    let mdstat = read_to_string("/proc/mdstat").unwrap_or_default();
+    parse_mdstat_arrays(&mdstat)
+        .filter_map(|array| {
+            let md = Path::new("/sys/block").join(array).join("md");
+            md.is_dir().then(|| md_array(array, &md))
+        })
+        .collect()
+}
+
+/// Each array starts with a line with the array name and members
+///
+/// ```text
+/// Personalities : [raid1]
+/// md0 : active raid1 sdb1[1] sda1[0]
+///       976630464 blocks super 1.2 [2/2] [UU]
+///       bitmap: 0/8 pages [0KB], 65536KB chunk
+/// ```
+fn parse_mdstat_arrays(mdstat: &str) -> impl Iterator<Item = &str> {
+    mdstat.lines().filter_map(|line| {
+        let (array, _) = line.split_once(" : ")?;
+        array.starts_with("md").then_some(array)
+    })
+}
+
+fn md_array(array: &str, md: &Path) -> MdArray {
+    let sync_action = read_trimmed(md.join("sync_action")).unwrap_or_default();
+    let syncing = !sync_action.is_empty() && sync_action != "idle";
+
+    // "completed / total" in sectors, or "none" when no sync is running
+    let sync_progress = syncing
+        .then(|| {
+            let completed = read_trimmed(md.join("sync_completed"))?;
+            let (done, total) = completed.split_once(" / ")?;
+            let done: f64 = done.parse().ok()?;
+            let total: f64 = total.parse().ok()?;
+            (total > 0.0).then(|| done / total)
+        })
+        .flatten();
+    // in KiB/s, or "none" when no sync is running
+    let sync_speed = syncing
+        .then(|| read_num::<u64>(md.join("sync_speed")))
+        .flatten()
+        .map(|speed| speed * 1024);
+
+    let mut members: Vec<MdMember> = read_dir(md)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let device = name.strip_prefix("dev-")?.to_string();
+            Some(MdMember {
+                device,
+                state: read_trimmed(entry.path().join("state")).unwrap_or_default(),
+            })
+        })
+        .collect();
+    members.sort_by(|a, b| a.device.cmp(&b.device));
+
+    MdArray {
+        array: array.into(),
+        level: read_trimmed(md.join("level")).unwrap_or_default(),
+        state: read_trimmed(md.join("array_state")).unwrap_or_default(),
+        degraded: read_num(md.join("degraded")).unwrap_or_default(),
+        raid_disks: read_num(md.join("raid_disks")).unwrap_or_default(),
+        sync_action,
+        sync_progress,
+        sync_speed,
+        members,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(device: &str, state: &str) -> MdMember {
+        MdMember {
+            device: device.into(),
+            state: state.into(),
+        }
+    }
+
+    #[test]
+    fn test_degraded_raid1_disk_counts() {
+        // raid1 clears in_sync when marking a member as faulty
+        let array = MdArray {
+            array: "md0".into(),
+            level: "raid1".into(),
+            state: "clean".into(),
+            degraded: 1,
+            raid_disks: 2,
+            sync_action: "idle".into(),
+            members: vec![member("sda1", "in_sync"), member("sdb1", "faulty")],
+            ..MdArray::default()
+        };
+        let mut out = String::new();
+        array.write(&mut out, "host");
+        assert!(out.contains(r#"md_disks{host="host", array="md0"} 2"#));
+        assert!(out.contains(r#"md_disks_active{host="host", array="md0"} 1"#));
+        assert!(out.contains(r#"md_disks_failed{host="host", array="md0"} 1"#));
+        assert!(out.contains(r#"md_disks_spare{host="host", array="md0"} 0"#));
+        assert!(out.contains(r#"md_member_in_sync{host="host", array="md0", member="sda1"} 1"#));
+        assert!(out.contains(r#"md_member_in_sync{host="host", array="md0", member="sdb1"} 0"#));
+    }
+
+    #[test]
+    fn test_faulty_member_still_in_sync() {
+        let array = MdArray {
+            array: "md1".into(),
+            raid_disks: 3,
+            members: vec![
+                member("sda2", "in_sync"),
+                member("sdb2", "in_sync"),
+                member("sdc2", "faulty,in_sync"),
+            ],
+            ..MdArray::default()
+        };
+        let mut out = String::new();
+        array.write(&mut out, "host");
+        assert!(out.contains(r#"md_disks_active{host="host", array="md1"} 2"#));
+        assert!(out.contains(r#"md_disks_failed{host="host", array="md1"} 1"#));
+    }
+
+    #[test]
+    fn test_parse_mdstat_arrays() {
+        let mdstat = "Personalities : [raid1] [raid6] [raid5] [raid4]\n\
+            md127 : active raid5 sdc1[2] sdb1[1] sda1[0]\n      \
+            1953260544 blocks super 1.2 level 5, 512k chunk, algorithm 2 [3/3] [UUU]\n\
+            \n\
+            md0 : active raid1 sde1[1](F) sdd1[0]\n      \
+            976630464 blocks super 1.2 [2/1] [U_]\n\
+            \n\
+            unused devices: <none>\n";
+        assert_eq!(
+            parse_mdstat_arrays(mdstat).collect::<Vec<_>>(),
+            vec!["md127", "md0"]
+        );
+    }
+}
@@ -1,28 +1,126 @@
 use crate::data::{DiskStats, DiskUsage};
+use crate::linux::filter::NameFilter;
 use crate::{Error, IoResultExt, MultiSensorSource, Result};
-use ahash::{AHashSet, AHasher};
-use regex::Regex;
+use ahash::{AHashMap, AHashSet, AHasher};
 use std::ffi::CString;
-use std::fs::File;
+use std::fs::{metadata, read_dir, read_link, read_to_string, File};
 use std::hash::{Hash, Hasher};
 use std::io::{Read, Seek};
 use std::mem::MaybeUninit;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::{Duration, Instant};
 use tracing::{debug, error};
 
+pub mod btrfs;
+pub mod info;
+pub mod lvm;
+pub mod md;
+pub mod smart;
+pub mod temperature;
 pub mod zfs;
 
+/// Selects the block devices to report
+#[derive(Debug, Clone)]
+pub struct DiskFilter {
+    names: NameFilter,
+    physical_only: bool,
+}
+
+impl DiskFilter {
+    /// Configured with `DISK_INCLUDE`, `DISK_EXCLUDE` and `DISK_PHYSICAL_ONLY`
+    ///
+    /// By default whole disks, device-mapper, md raid, zvol and bcache devices are included,
+    /// unless only physical disks are selected.
+    pub fn from_env() -> Result<DiskFilter> {
+        let physical_only = dotenvy::var("DISK_PHYSICAL_ONLY").is_ok();
+        let default_include = (!physical_only).then_some(
+            r"^([shv]d[a-z]+|xvd[a-z]+|nvme[0-9]+n[0-9]+|mmcblk[0-9]+|dm-[0-9]+|md[0-9]+|zd[0-9]+|bcache[0-9]+)$",
+        );
+        Ok(DiskFilter {
+            names: NameFilter::from_env("DISK", default_include, None)?,
+            physical_only,
+        })
+    }
+
+    pub fn matches(&self, disk: &str) -> bool {
+        self.names.matches(disk) && (!self.physical_only || is_physical(disk))
+    }
+}
+
+/// Whole disks are backed by a device, partitions and virtual devices like device-mapper or loop devices are not
+pub(crate) fn is_physical(disk: &str) -> bool {
+    Path::new("/sys/block").join(disk).join("device").exists()
+}
+
+/// `/dev/disk/by-id` names that are derived from identifiers instead of the model and serial
+const GENERATED_ID_PREFIXES: &[&str] = &[
+    "wwn-",
+    "nvme-eui.",
+    "nvme-nvme.",
+    "dm-uuid-",
+    "md-uuid-",
+    "lvm-pv-uuid-",
+];
+
+/// Find friendly names for block devices
+///
+/// Device-mapper devices use their name from `/dev/mapper`, other devices use the
+/// name from `/dev/disk/by-id`, preferring model and serial based names over wwn and uuid based ones.
+fn disk_aliases() -> AHashMap<String, String> {
+    let mut aliases: AHashMap<String, String> = AHashMap::new();
+    for link in read_dir("/dev/disk/by-id").into_iter().flatten().flatten() {
+        let (Ok(alias), Ok(target)) = (link.file_name().into_string(), read_link(link.path()))
+        else {
+            continue;
+        };
+        let Some(disk) = target.file_name().and_then(|disk| disk.to_str()) else {
+            continue;
+        };
+        let rank = |alias: &str| {
+            let generated = GENERATED_ID_PREFIXES
+                .iter()
+                .any(|prefix| alias.starts_with(prefix));
+            (generated, alias.len())
+        };
+        match aliases.get(disk) {
+            Some(existing) if rank(existing) <= rank(&alias) => {}
+            _ => {
+                aliases.insert(disk.to_string(), alias);
+            }
+        }
+    }
+
+    for disk in read_dir("/sys/block").into_iter().flatten().flatten() {
+        if let (Ok(name), Ok(disk)) = (
+            read_to_string(disk.path().join("dm/name")),
+            disk.file_name().into_string(),
+        ) {
+            aliases.insert(disk, name.trim().to_string());
+        }
+    }
+    aliases
+}
+
+/// How long the disk aliases are cached before rescanning `/dev/disk/by-id`
+const ALIAS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
 pub struct DiskStatSource {
     source: File,
     buff: String,
-    regex: Regex,
+    filter: DiskFilter,
+    aliases: AHashMap<String, String>,
+    aliases_read: Option<Instant>,
 }
 
 impl DiskStatSource {
-    pub fn new() -> Result<DiskStatSource> {
+    pub fn new(filter: DiskFilter) -> Result<DiskStatSource> {
         Ok(DiskStatSource {
             source: File::open("/proc/diskstats").context("error getting disk stats")?,
             buff: String::new(),
-            regex: Regex::new(r" ([sv]d[a-z]+|nvme[0-9]n[0-9]|mmcblk[0-9]) ").unwrap(),
+            filter,
+            aliases: AHashMap::new(),
+            aliases_read: None,
         })
     }
 }
@@ -38,55 +136,136 @@ impl MultiSensorSource for DiskStatSource {
             .read_to_string(&mut self.buff)
             .context("error reading disk stats")?;
 
+        if self
+            .aliases_read
+            .map_or(true, |read| read.elapsed() > ALIAS_REFRESH_INTERVAL)
+        {
+            self.aliases = disk_aliases();
+            self.aliases_read = Some(Instant::now());
+        }
+
         Ok(DiskStatParser {
             lines: self.buff.lines(),
-            regex: &self.regex,
+            filter: &self.filter,
+            aliases: &self.aliases,
         })
     }
 }
 
 pub struct DiskStatParser<'a> {
     lines: std::str::Lines<'a>,
-    regex: &'a Regex,
+    filter: &'a DiskFilter,
+    aliases: &'a AHashMap<String, String>,
 }
 
 impl Iterator for DiskStatParser<'_> {
     type Item = Result<DiskStats>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let line = loop {
+        let mut parts = loop {
             let line = self.lines.next()?;
-            if self.regex.is_match(line) {
-                break line;
+            let parts = line.split_whitespace().skip(2);
+            if parts
+                .clone()
+                .next()
+                .is_some_and(|name| self.filter.matches(name))
+            {
+                break parts;
             }
         };
-        let mut parts = line.split_whitespace().skip(2);
         let name: String = parts.next()?.into();
-        let _read_count = parts.next();
-        let _read_merged_count = parts.next();
-        let read_sectors = parts.next()?.parse::<u64>().ok()?;
-        let mut parts = parts.skip(1);
-        let _write_count = parts.next();
-        let _write_merged_count = parts.next();
-        let write_sectors = parts.next()?.parse::<u64>().ok()?;
+        let mut next = || parts.next().and_then(|value| value.parse::<u64>().ok());
+        // fields are evaluated in order, matching the column order in diskstats
+        // the discard and flush columns are missing on older kernels
         Some(Ok(DiskStats {
+            alias: self.aliases.get(&name).cloned(),
             interface: name,
-            bytes_sent: write_sectors * 512,
-            bytes_received: read_sectors * 512,
+            reads: next()?,
+            reads_merged: next()?,
+            bytes_received: next()? * 512,
+            read_time: next()?,
+            writes: next()?,
+            writes_merged: next()?,
+            bytes_sent: next()? * 512,
+            write_time: next()?,
+            in_flight: next()?,
+            io_time: next()?,
+            weighted_io_time: next()?,
+            discards: next().unwrap_or_default(),
+            discards_merged: next().unwrap_or_default(),
+            bytes_discarded: next().unwrap_or_default() * 512,
+            discard_time: next().unwrap_or_default(),
+            flushes: next().unwrap_or_default(),
+            flush_time: next().unwrap_or_default(),
         }))
     }
 }
 
+/// Filesystem types that don't represent storage, or that are reported separately
+const DEFAULT_FSTYPE_EXCLUDE: &str = r"^(autofs|binfmt_misc|bpf|cgroup2?|configfs|debugfs|devpts|devtmpfs|efivarfs|fusectl|hugetlbfs|mqueue|nsfs|overlay|proc|pstore|ramfs|rpc_pipefs|securityfs|squashfs|sysfs|tmpfs|tracefs|zfs|nfs4?|cifs|smb3|fuse\.(gvfsd-fuse|portal|lxcfs|snapfuse))$";
+
+/// How mounts of the same filesystem are deduplicated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MountDedupe {
+    /// Mounts with the same source device
+    Device,
+    /// Mounts with the same `st_dev`, bind mounts share this but btrfs subvolumes don't
+    StDev,
+    None,
+}
+
+/// Selects the mounts to report
+#[derive(Debug, Clone)]
+pub struct MountFilter {
+    mount_points: NameFilter,
+    devices: NameFilter,
+    fstypes: NameFilter,
+    dedupe: MountDedupe,
+}
+
+impl MountFilter {
+    /// Configured with `MOUNT_INCLUDE`/`MOUNT_EXCLUDE` for the mount point, `MOUNT_DEVICE_INCLUDE`/`MOUNT_DEVICE_EXCLUDE`
+    /// for the device, `MOUNT_FSTYPE_INCLUDE`/`MOUNT_FSTYPE_EXCLUDE` for the filesystem type and `MOUNT_DEDUPE`
+    ///
+    /// By default loop devices and pseudo filesystems are excluded and mounts are deduplicated by `st_dev`.
+    pub fn from_env() -> Result<MountFilter> {
+        let dedupe = match dotenvy::var("MOUNT_DEDUPE").as_deref() {
+            Ok("device") => MountDedupe::Device,
+            Ok("stdev" | "") | Err(_) => MountDedupe::StDev,
+            Ok("none") => MountDedupe::None,
+            Ok(mode) => {
+                return Err(Error::Other(format!(
+                    "Invalid MOUNT_DEDUPE mode {mode}, expected one of device, stdev or none"
+                )))
+            }
+        };
+        Ok(MountFilter {
+            mount_points: NameFilter::from_env("MOUNT", None, None)?,
+            devices: NameFilter::from_env("MOUNT_DEVICE", None, Some("^/dev/loop"))?,
+            fstypes: NameFilter::from_env("MOUNT_FSTYPE", None, Some(DEFAULT_FSTYPE_EXCLUDE))?,
+            dedupe,
+        })
+    }
+
+    pub fn matches(&self, device: &str, mount_point: &str, fstype: &str) -> bool {
+        self.mount_points.matches(mount_point)
+            && self.devices.matches(device)
+            && self.fstypes.matches(fstype)
+    }
+}
+
 pub struct DiskUsageSource {
     source: File,
     buff: String,
+    filter: MountFilter,
 }
 
 impl DiskUsageSource {
-    pub fn new() -> Result<DiskUsageSource> {
+    pub fn new(filter: MountFilter) -> Result<DiskUsageSource> {
         Ok(DiskUsageSource {
             source: File::open("/proc/mounts").context("error opening mounts")?,
             buff: String::new(),
+            filter,
         })
     }
 }
@@ -104,6 +283,7 @@ impl MultiSensorSource for DiskUsageSource {
 
         Ok(DiskUsageParser {
             lines: self.buff.lines(),
+            filter: &self.filter,
             found_disks: AHashSet::with_capacity(16),
         })
     }
@@ -111,33 +291,45 @@ impl MultiSensorSource for DiskUsageSource {
 
 pub struct DiskUsageParser<'a> {
     lines: std::str::Lines<'a>,
+    filter: &'a MountFilter,
     found_disks: AHashSet<u64>,
 }
 
+impl DiskUsageParser<'_> {
+    /// Check if we've seen the filesystem of this mount before
+    fn is_duplicate(&mut self, device: &str, mount_point: &str) -> bool {
+        let key = match self.filter.dedupe {
+            MountDedupe::Device => hash_str(device),
+            MountDedupe::StDev => match metadata(mount_point) {
+                Ok(metadata) => metadata.dev(),
+                Err(_) => return false,
+            },
+            MountDedupe::None => return false,
+        };
+        !self.found_disks.insert(key)
+    }
+}
+
 impl Iterator for DiskUsageParser<'_> {
     type Item = Result<DiskUsage>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mount_point = loop {
+        let (device, mount_point, fstype) = loop {
             let line = self.lines.next()?;
-            if line.starts_with('/')
-                && !line.contains("/dev/loop")
-                && !line.contains("fuse")
-                && !line.contains("squashfs")
-            {
-                debug!(line, "picking mount");
-
-                let mut parts = line.split_ascii_whitespace();
-                let disk = parts.next()?;
-                if self.found_disks.insert(hash_str(disk)) {
-                    let mount_point = parts.next()?;
+            let mut parts = line.split_ascii_whitespace();
+            let (Some(device), Some(mount_point), Some(fstype)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
 
-                    break mount_point;
-                } else {
-                    debug!(line, "skipping already processed disk");
-                }
-            } else {
+            if !self.filter.matches(device, mount_point, fstype) {
                 debug!(line, "skipping mount");
+            } else if self.is_duplicate(device, mount_point) {
+                debug!(line, "skipping already processed disk");
+            } else {
+                debug!(line, "picking mount");
+                break (device, mount_point, fstype);
             }
         };
 
@@ -152,8 +344,14 @@ impl Iterator for DiskUsageParser<'_> {
         #[allow(clippy::unnecessary_cast)]
         Some(Ok(DiskUsage {
             name: mount_point.to_string(),
+            device: device.to_string(),
+            fstype: fstype.to_string(),
             size: stat.f_blocks * stat.f_frsize as u64,
             free: stat.f_bavail * stat.f_frsize as u64,
+            reserved: Some(stat.f_bfree.saturating_sub(stat.f_bavail) * stat.f_frsize as u64),
+            inodes: stat.f_files as u64,
+            inodes_free: stat.f_ffree as u64,
+            readonly: Some(stat.f_flag & libc::ST_RDONLY != 0),
         }))
     }
 }
@@ -176,3 +374,47 @@ fn hash_str(s: &str) -> u64 {
     s.hash(&mut hasher);
     hasher.finish()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SensorData;
+
+    #[test]
+    fn test_parse_with_alias() {
+        let diskstats = "   8       0 sda 100 2 800 30 50 1 400 20 0 40 50 0 0 0 0 0 0\n \
+            253       0 dm-0 10 0 80 3 5 0 40 2 0 4 5\n   \
+            7       0 loop0 1 0 8 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n";
+        let filter = DiskFilter {
+            names: NameFilter::from_env("DISK_TEST_PARSE", Some("^(sd|dm-)"), None).unwrap(),
+            physical_only: false,
+        };
+        let aliases = [("dm-0".to_string(), "vg-root \"x\"".to_string())]
+            .into_iter()
+            .collect();
+        let parser = DiskStatParser {
+            lines: diskstats.lines(),
+            filter: &filter,
+            aliases: &aliases,
+        };
+        let disks = parser.collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(disks.len(), 2);
+        assert_eq!(disks[0].interface, "sda");
+        assert_eq!(disks[0].alias, None);
+        assert_eq!(disks[0].bytes_received, 800 * 512);
+        assert_eq!(disks[1].interface, "dm-0");
+        // older kernels don't have the discard and flush columns
+        assert_eq!(disks[1].flushes, 0);
+
+        let mut out = String::new();
+        disks[1].write(&mut out, "host");
+        assert!(out.contains(r#"disk_sent{host="host", disk="dm-0", alias="vg-root \"x\""} 20480"#));
+        assert!(out.contains(r#"disk_reads{host="host", disk="dm-0", alias="vg-root \"x\""} 10"#));
+        assert!(out.contains(r#"disk_alias{host="host", disk="dm-0", alias="vg-root \"x\""} 1"#));
+
+        let mut out = String::new();
+        disks[0].write(&mut out, "host");
+        assert!(out.contains(r#"disk_reads{host="host", disk="sda"} 100"#));
+        assert!(!out.contains("disk_alias"));
+    }
+}
@@ -0,0 +1,340 @@
+use crate::linux::disk::{is_physical, DiskFilter};
+use crate::SensorData;
+use serde::Deserialize;
+use std::fmt::Write;
+use std::fs::read_dir;
+use std::io::ErrorKind;
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+static SMART_DATA: Mutex<Vec<SmartData>> = Mutex::new(Vec::new());
+
+/// smartctl exit status bit for when the device couldn't be opened
+const EXIT_OPEN_FAILED: i32 = 1 << 1;
+
+/// Don't wake up disks that are spun down, smartctl exits with status 0 if the disk is in standby
+const POWER_MODE_CHECK: &str = "standby,0";
+
+/// Smart attribute ids for ata devices
+const ATTRIBUTE_REALLOCATED_SECTORS: u32 = 5;
+const ATTRIBUTE_PENDING_SECTORS: u32 = 197;
+const ATTRIBUTE_OFFLINE_UNCORRECTABLE: u32 = 198;
+
+#[derive(Debug, Deserialize)]
+struct SmartctlOutput {
+    #[serde(default)]
+    smartctl: SmartctlInfo,
+    smart_status: Option<SmartStatus>,
+    temperature: Option<Temperature>,
+    power_on_time: Option<PowerOnTime>,
+    ata_smart_attributes: Option<AtaAttributes>,
+    nvme_smart_health_information_log: Option<NvmeHealth>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SmartctlInfo {
+    #[serde(default)]
+    messages: Vec<SmartctlMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SmartctlMessage {
+    string: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SmartStatus {
+    passed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Temperature {
+    current: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PowerOnTime {
+    hours: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AtaAttributes {
+    table: Vec<AtaAttribute>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AtaAttribute {
+    id: u32,
+    raw: AtaRawValue,
+}
+
+#[derive(Debug, Deserialize)]
+struct AtaRawValue {
+    value: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct NvmeHealth {
+    percentage_used: Option<u64>,
+    media_errors: Option<u64>,
+    unsafe_shutdowns: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SmartData {
+    disk: String,
+    healthy: Option<bool>,
+    temperature: Option<i64>,
+    power_on_hours: Option<u64>,
+    reallocated_sectors: Option<u64>,
+    pending_sectors: Option<u64>,
+    offline_uncorrectable: Option<u64>,
+    percentage_used: Option<u64>,
+    media_errors: Option<u64>,
+    unsafe_shutdowns: Option<u64>,
+}
+
+impl SensorData for SmartData {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        if let Some(temperature) = self.temperature {
+            writeln!(
+                &mut w,
+                r#"smart_temperature{{host="{}", disk="{}"}} {}"#,
+                hostname, self.disk, temperature
+            )
+            .ok();
+        }
+        let values = [
+            ("healthy", self.healthy.map(u64::from)),
+            ("power_on_hours", self.power_on_hours),
+            ("reallocated_sectors", self.reallocated_sectors),
+            ("pending_sectors", self.pending_sectors),
+            ("offline_uncorrectable", self.offline_uncorrectable),
+            ("percentage_used", self.percentage_used),
+            ("media_errors", self.media_errors),
+            ("unsafe_shutdowns", self.unsafe_shutdowns),
+        ];
+        for (name, value) in values {
+            if let Some(value) = value {
+                writeln!(
+                    &mut w,
+                    r#"smart_{}{{host="{}", disk="{}"}} {}"#,
+                    name, hostname, self.disk, value
+                )
+                .ok();
+            }
+        }
+    }
+}
+
+fn parse_output(disk: &str, output: &SmartctlOutput) -> SmartData {
+    let attribute = |id: u32| {
+        output.ata_smart_attributes.as_ref().and_then(|attributes| {
+            attributes
+                .table
+                .iter()
+                .find(|attribute| attribute.id == id)
+                .map(|attribute| attribute.raw.value)
+        })
+    };
+    let nvme = output.nvme_smart_health_information_log.as_ref();
+
+    SmartData {
+        disk: disk.into(),
+        healthy: output.smart_status.as_ref().map(|status| status.passed),
+        temperature: output.temperature.as_ref().and_then(|temp| temp.current),
+        power_on_hours: output.power_on_time.as_ref().and_then(|time| time.hours),
+        reallocated_sectors: attribute(ATTRIBUTE_REALLOCATED_SECTORS),
+        pending_sectors: attribute(ATTRIBUTE_PENDING_SECTORS),
+        offline_uncorrectable: attribute(ATTRIBUTE_OFFLINE_UNCORRECTABLE),
+        percentage_used: nvme.and_then(|nvme| nvme.percentage_used),
+        media_errors: nvme.and_then(|nvme| nvme.media_errors),
+        unsafe_shutdowns: nvme.and_then(|nvme| nvme.unsafe_shutdowns),
+    }
+}
+
+enum SmartError {
+    /// smartctl can't be used at all, either because it's not installed or we lack permissions
+    Unavailable(String),
+    /// The device can't be queried
+    Device(String),
+    /// The device is spun down and was skipped to not wake it up
+    Standby,
+}
+
+fn smartctl(disk: &str) -> Result<SmartData, SmartError> {
+    let out = Command::new("smartctl")
+        .args(["--json", "-a", "-n", POWER_MODE_CHECK])
+        .arg(format!("/dev/{disk}"))
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => SmartError::Unavailable("smartctl not found".into()),
+            _ => SmartError::Device(e.to_string()),
+        })?;
+    parse_result(disk, out.status.code().unwrap_or(-1), &out.stdout)
+}
+
+fn parse_result(disk: &str, status: i32, stdout: &[u8]) -> Result<SmartData, SmartError> {
+    let output: SmartctlOutput =
+        serde_json::from_slice(stdout).map_err(|e| SmartError::Device(e.to_string()))?;
+    let mut messages = output
+        .smartctl
+        .messages
+        .iter()
+        .map(|message| message.string.as_str());
+
+    // the exit status is a bitmask, non-zero values are also used to report disk problems
+    if status < 0 || status & EXIT_OPEN_FAILED != 0 {
+        let messages = messages.collect::<Vec<_>>().join(", ");
+        return Err(if messages.contains("Permission denied") {
+            SmartError::Unavailable(messages)
+        } else {
+            SmartError::Device(messages)
+        });
+    }
+    // "Device is in STANDBY mode, exit(0)"
+    if messages.any(|message| message.starts_with("Device is in ")) {
+        return Err(SmartError::Standby);
+    }
+    Ok(parse_output(disk, &output))
+}
+
+/// Run smartctl for all physical disks selected by the disk filter, the smart data is read with the interval
+/// configured by `SMART_INTERVAL` in seconds (default 300)
+///
+/// When smartctl isn't installed or lacks the permissions to read the disks, a warning is logged and the
+/// collector stops. Disks that are spun down aren't woken up, the previous data is kept for them instead.
+pub fn update_smart(filter: DiskFilter) {
+    let interval = dotenvy::var("SMART_INTERVAL")
+        .ok()
+        .and_then(|interval| interval.parse().ok())
+        .unwrap_or(300);
+    loop {
+        let disks = read_dir("/sys/block")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|disk| filter.matches(disk) && is_physical(disk));
+
+        let previous = smart_data();
+        let mut results = Vec::new();
+        for disk in disks {
+            match smartctl(&disk) {
+                Ok(data) => results.push(data),
+                Err(SmartError::Standby) => {
+                    debug!(disk, "Disk is in standby, keeping previous smart data");
+                    results.extend(previous.iter().find(|data| data.disk == disk).cloned());
+                }
+                Err(SmartError::Unavailable(error)) => {
+                    warn!(
+                        error,
+                        "Failed to read smart data, disabling smart monitoring"
+                    );
+                    SMART_DATA.lock().unwrap().clear();
+                    return;
+                }
+                Err(SmartError::Device(error)) => {
+                    debug!(error, disk, "Failed to read smart data");
+                }
+            }
+        }
+        *SMART_DATA.lock().unwrap() = results;
+        sleep(Duration::from_secs(interval));
+    }
+}
+
+/// The last read smart data
+pub fn smart_data() -> Vec<SmartData> {
+    SMART_DATA.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATA: &str = include_str!("../../../tests/fixtures/smart/ata.json");
+    const NVME: &str = include_str!("../../../tests/fixtures/smart/nvme.json");
+    const PERMISSION_DENIED: &str =
+        include_str!("../../../tests/fixtures/smart/permission_denied.json");
+    const STANDBY: &str = include_str!("../../../tests/fixtures/smart/standby.json");
+
+    #[test]
+    fn test_parse_ata() {
+        let Ok(data) = parse_result("sda", 0, ATA.as_bytes()) else {
+            panic!("failed to parse ata output");
+        };
+        assert_eq!(data.disk, "sda");
+        assert_eq!(data.healthy, Some(true));
+        assert_eq!(data.temperature, Some(36));
+        assert_eq!(data.power_on_hours, Some(38912));
+        assert_eq!(data.reallocated_sectors, Some(3));
+        assert_eq!(data.pending_sectors, Some(1));
+        assert_eq!(data.offline_uncorrectable, Some(0));
+        assert_eq!(data.percentage_used, None);
+        assert_eq!(data.media_errors, None);
+        assert_eq!(data.unsafe_shutdowns, None);
+    }
+
+    #[test]
+    fn test_parse_nvme() {
+        let Ok(data) = parse_result("nvme0n1", 0, NVME.as_bytes()) else {
+            panic!("failed to parse nvme output");
+        };
+        assert_eq!(data.disk, "nvme0n1");
+        assert_eq!(data.healthy, Some(true));
+        assert_eq!(data.temperature, Some(43));
+        assert_eq!(data.power_on_hours, Some(9214));
+        assert_eq!(data.reallocated_sectors, None);
+        assert_eq!(data.pending_sectors, None);
+        assert_eq!(data.percentage_used, Some(4));
+        assert_eq!(data.media_errors, Some(0));
+        assert_eq!(data.unsafe_shutdowns, Some(57));
+    }
+
+    #[test]
+    fn test_disk_problems_are_not_errors() {
+        // bit 3: "SMART status check returned DISK FAILING"
+        assert!(parse_result("sda", 1 << 3, ATA.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_permission_denied() {
+        assert!(matches!(
+            parse_result("sda", 2, PERMISSION_DENIED.as_bytes()),
+            Err(SmartError::Unavailable(message)) if message.contains("Permission denied")
+        ));
+    }
+
+    #[test]
+    fn test_standby() {
+        assert!(matches!(
+            parse_result("sdb", 0, STANDBY.as_bytes()),
+            Err(SmartError::Standby)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_output() {
+        assert!(matches!(
+            parse_result("sda", 0, b"not json"),
+            Err(SmartError::Device(_))
+        ));
+    }
+
+    #[test]
+    fn test_write() {
+        let Ok(data) = parse_result("nvme0n1", 0, NVME.as_bytes()) else {
+            panic!("failed to parse nvme output");
+        };
+        let mut out = String::new();
+        data.write(&mut out, "host");
+        assert!(out.contains(r#"smart_temperature{host="host", disk="nvme0n1"} 43"#));
+        assert!(out.contains(r#"smart_healthy{host="host", disk="nvme0n1"} 1"#));
+        assert!(out.contains(r#"smart_percentage_used{host="host", disk="nvme0n1"} 4"#));
+        assert!(!out.contains("smart_reallocated_sectors"));
+    }
+}
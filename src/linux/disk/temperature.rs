@@ -0,0 +1,129 @@
+use crate::linux::hwmon::{Device, FileSource};
+use crate::{IoResultExt, MultiSensorSource, Result, SensorData};
+use std::fmt::Write;
+use std::fs::read_dir;
+use std::path::Path;
+use std::vec::IntoIter;
+
+#[derive(Debug)]
+pub struct DriveTemperature {
+    disk: String,
+    sensor: String,
+    temperature: f32,
+    warning: Option<f32>,
+    critical: Option<f32>,
+}
+
+impl SensorData for DriveTemperature {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"drive_temperature{{host="{}", disk="{}", sensor="{}"}} {:.1}"#,
+            hostname, self.disk, self.sensor, self.temperature
+        )
+        .ok();
+        if let Some(warning) = self.warning {
+            writeln!(
+                &mut w,
+                r#"drive_temperature_warning{{host="{}", disk="{}", sensor="{}"}} {:.1}"#,
+                hostname, self.disk, self.sensor, warning
+            )
+            .ok();
+        }
+        if let Some(critical) = self.critical {
+            writeln!(
+                &mut w,
+                r#"drive_temperature_critical{{host="{}", disk="{}", sensor="{}"}} {:.1}"#,
+                hostname, self.disk, self.sensor, critical
+            )
+            .ok();
+        }
+    }
+}
+
+struct DriveSensor {
+    disk: String,
+    sensor: String,
+    reader: FileSource,
+    warning: Option<f32>,
+    critical: Option<f32>,
+}
+
+/// Temperatures of nvme and sata drives from the `nvme` and `drivetemp` hwmon devices
+pub struct DriveTemperatureSource {
+    sensors: Vec<DriveSensor>,
+}
+
+impl DriveTemperatureSource {
+    pub fn new() -> Result<DriveTemperatureSource> {
+        let mut sensors = Vec::new();
+        for device in Device::list().flatten() {
+            if device.name() != "nvme" && device.name() != "drivetemp" {
+                continue;
+            }
+            let Some(disk) = block_device(device.path()) else {
+                continue;
+            };
+            for sensor in device.sensors().flatten() {
+                let reader = sensor.reader().context("error opening drive temp sensor")?;
+                // thresholds are in millidegrees like the temperature itself
+                sensors.push(DriveSensor {
+                    disk: disk.clone(),
+                    sensor: sensor.name().into(),
+                    reader,
+                    warning: sensor.attribute::<f32>("max").map(|max| max / 1000.0),
+                    critical: sensor.attribute::<f32>("crit").map(|crit| crit / 1000.0),
+                });
+            }
+        }
+        Ok(DriveTemperatureSource { sensors })
+    }
+}
+
+impl MultiSensorSource for DriveTemperatureSource {
+    type Data = DriveTemperature;
+    type Iter<'a> = IntoIter<Result<DriveTemperature>>;
+
+    fn read(&mut self) -> Result<Self::Iter<'_>> {
+        Ok(self
+            .sensors
+            .iter_mut()
+            .map(|sensor| {
+                let temperature = sensor
+                    .reader
+                    .read::<f32>()
+                    .context("error reading drive temp sensor")?;
+                Ok(DriveTemperature {
+                    disk: sensor.disk.clone(),
+                    sensor: sensor.sensor.clone(),
+                    temperature: temperature / 1000.0,
+                    warning: sensor.warning,
+                    critical: sensor.critical,
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+}
+
+/// Find the block device for a hwmon device through its `device` link
+///
+/// The device of a drivetemp sensor is the scsi device, which lists its disk in the `block` directory,
+/// the device of an nvme sensor is the nvme controller, which contains its namespaces.
+fn block_device(hwmon: &Path) -> Option<String> {
+    let device = hwmon.join("device");
+    for dir in [device.join("block"), device] {
+        let mut disks: Vec<String> = read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| Path::new("/sys/block").join(name).exists())
+            .collect();
+        disks.sort();
+        if let Some(disk) = disks.into_iter().next() {
+            return Some(disk);
+        }
+    }
+    None
+}
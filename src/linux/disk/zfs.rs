@@ -43,7 +43,14 @@ fn parse_line(line: &str) -> Option<DiskUsage> {
     let name = parts.next()?.to_string();
     let size = parts.next()?.parse().ok()?;
     let free = parts.next()?.parse().ok()?;
-    Some(DiskUsage { name, size, free })
+    Some(DiskUsage {
+        device: name.clone(),
+        fstype: "zfs".into(),
+        name,
+        size,
+        free,
+        ..DiskUsage::default()
+    })
 }
 
 #[derive(Default)]
@@ -152,3 +159,21 @@ pub fn arcstats() -> Option<ArcStats> {
 
     Some(stats)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SensorData;
+
+    #[test]
+    fn test_pool_usage() {
+        let pool = parse_line("tank\t3985729650688\t1523425476608\n").unwrap();
+        let mut out = String::new();
+        pool.write(&mut out, "host");
+        assert_eq!(
+            out,
+            "disk_size{host=\"host\", disk=\"tank\", device=\"tank\", fstype=\"zfs\"} 3985729650688\n\
+             disk_free{host=\"host\", disk=\"tank\", device=\"tank\", fstype=\"zfs\"} 1523425476608\n"
+        );
+    }
+}
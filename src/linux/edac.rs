@@ -0,0 +1,148 @@
+use crate::linux::hwmon::read_num;
+use crate::SensorData;
+use std::fmt::Write;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct CsRow {
+    name: String,
+    correctable: u64,
+    uncorrectable: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryController {
+    name: String,
+    correctable: u64,
+    uncorrectable: u64,
+    correctable_noinfo: u64,
+    uncorrectable_noinfo: u64,
+    rows: Vec<CsRow>,
+}
+
+impl SensorData for MemoryController {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        let values = [
+            ("correctable_errors", self.correctable),
+            ("uncorrectable_errors", self.uncorrectable),
+            ("correctable_errors_noinfo", self.correctable_noinfo),
+            ("uncorrectable_errors_noinfo", self.uncorrectable_noinfo),
+        ];
+        for (name, value) in values {
+            writeln!(
+                &mut w,
+                r#"edac_{}{{host="{}", controller="{}"}} {}"#,
+                name, hostname, self.name, value
+            )
+            .ok();
+        }
+        for row in &self.rows {
+            writeln!(
+                &mut w,
+                r#"edac_csrow_correctable_errors{{host="{}", controller="{}", csrow="{}"}} {}"#,
+                hostname, self.name, row.name, row.correctable
+            )
+            .ok();
+            writeln!(
+                &mut w,
+                r#"edac_csrow_uncorrectable_errors{{host="{}", controller="{}", csrow="{}"}} {}"#,
+                hostname, self.name, row.name, row.uncorrectable
+            )
+            .ok();
+        }
+    }
+}
+
+/// List all edac memory controllers, errors counts are always reported, even when zero
+pub fn memory_controllers() -> Vec<MemoryController> {
+    read_dir("/sys/devices/system/edac/mc")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let controller = name.strip_prefix("mc")?;
+            if !controller.bytes().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let path = entry.path();
+            Some(MemoryController {
+                name: controller.into(),
+                correctable: read_num(path.join("ce_count")).unwrap_or_default(),
+                uncorrectable: read_num(path.join("ue_count")).unwrap_or_default(),
+                correctable_noinfo: read_num(path.join("ce_noinfo_count")).unwrap_or_default(),
+                uncorrectable_noinfo: read_num(path.join("ue_noinfo_count")).unwrap_or_default(),
+                rows: cs_rows(&path),
+            })
+        })
+        .collect()
+}
+
+fn cs_rows(controller: &Path) -> Vec<CsRow> {
+    read_dir(controller)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let row = name.strip_prefix("csrow")?;
+            let path = entry.path();
+            Some(CsRow {
+                name: row.into(),
+                correctable: read_num(path.join("ce_count")).unwrap_or_default(),
+                uncorrectable: read_num(path.join("ue_count")).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Default)]
+pub struct MachineChecks {
+    exceptions: u64,
+    polls: u64,
+}
+
+impl SensorData for MachineChecks {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"machine_check_exceptions{{host="{}"}} {}"#,
+            hostname, self.exceptions
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"machine_check_polls{{host="{}"}} {}"#,
+            hostname, self.polls
+        )
+        .ok();
+    }
+}
+
+/// Machine check event counts, for systems that support machine checks
+///
+/// The sysfs machine check interface only exposes the bank configuration, the event counters
+/// are exposed per-cpu in `/proc/interrupts`
+pub fn machine_checks() -> Option<MachineChecks> {
+    if !Path::new("/sys/devices/system/machine_check").exists() {
+        return None;
+    }
+    let interrupts = read_to_string("/proc/interrupts").ok()?;
+    let mut checks = MachineChecks::default();
+    for line in interrupts.lines() {
+        let Some((name, counts)) = line.trim_start().split_once(':') else {
+            continue;
+        };
+        let total = counts
+            .split_ascii_whitespace()
+            .map_while(|count| count.parse::<u64>().ok())
+            .sum();
+        match name {
+            "MCE" => checks.exceptions = total,
+            "MCP" => checks.polls = total,
+            _ => {}
+        }
+    }
+    Some(checks)
+}
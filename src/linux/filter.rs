@@ -0,0 +1,67 @@
+use crate::Result;
+use regex::Regex;
+
+/// Include and exclude patterns to select devices by name
+///
+/// The patterns are read from the `{PREFIX}_INCLUDE` and `{PREFIX}_EXCLUDE` environment variables,
+/// when not set the provided defaults are used.
+#[derive(Debug, Clone, Default)]
+pub struct NameFilter {
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+}
+
+impl NameFilter {
+    pub fn from_env(
+        prefix: &str,
+        default_include: Option<&str>,
+        default_exclude: Option<&str>,
+    ) -> Result<NameFilter> {
+        Ok(NameFilter {
+            include: env_regex(&format!("{prefix}_INCLUDE"), default_include)?,
+            exclude: env_regex(&format!("{prefix}_EXCLUDE"), default_exclude)?,
+        })
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.include
+            .as_ref()
+            .map_or(true, |include| include.is_match(name))
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|exclude| exclude.is_match(name))
+    }
+}
+
+fn env_regex(name: &str, default: Option<&str>) -> Result<Option<Regex>> {
+    match dotenvy::var(name) {
+        Ok(pattern) if pattern.is_empty() => Ok(None),
+        Ok(pattern) => Ok(Some(Regex::new(&pattern)?)),
+        Err(_) => Ok(default.map(Regex::new).transpose()?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_perl_classes() {
+        std::env::set_var("FILTER_TEST_PERL_INCLUDE", r"^veth\d+$");
+        std::env::set_var("FILTER_TEST_PERL_EXCLUDE", r"\s");
+        let filter = NameFilter::from_env("FILTER_TEST_PERL", None, None).unwrap();
+        assert!(filter.matches("veth12"));
+        assert!(!filter.matches("vethab"));
+        assert!(!filter.matches("eth0"));
+    }
+
+    #[test]
+    fn test_defaults_and_empty() {
+        std::env::set_var("FILTER_TEST_EMPTY_INCLUDE", "");
+        let filter =
+            NameFilter::from_env("FILTER_TEST_EMPTY", Some("^eth"), Some(r"^\w+\.\d+$")).unwrap();
+        assert!(filter.matches("wlan0"));
+        assert!(!filter.matches("eth0.10"));
+    }
+}
@@ -1,9 +1,8 @@
 use crate::data::{GpuMemory, GpuUsage};
-use crate::linux::hwmon::FileSource;
+use crate::linux::hwmon::{read_num, FileSource};
 use std::borrow::Cow;
-use std::fs::{read_dir, read_to_string};
+use std::fs::read_dir;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 use std::thread::sleep;
@@ -12,10 +11,6 @@ use tracing::{info, warn};
 
 pub mod nvidia;
 
-fn read_num<T: FromStr>(path: &str) -> Option<T> {
-    read_to_string(path).ok()?.trim().parse().ok()
-}
-
 pub fn memory() -> Option<GpuMemory> {
     if let Some(nv_mem) = nvidia::memory() {
         return Some(nv_mem);
@@ -12,6 +12,18 @@ fn read_to_string_trimmed(path: &Path) -> io::Result<String> {
     Ok(s)
 }
 
+/// Read a sysfs or procfs attribute, returning `None` when it can't be read or is empty
+pub(crate) fn read_trimmed(path: impl AsRef<Path>) -> Option<String> {
+    let s = read_to_string(path).ok()?;
+    let s = s.trim();
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// Read and parse a numeric sysfs or procfs attribute
+pub(crate) fn read_num<T: FromStr>(path: impl AsRef<Path>) -> Option<T> {
+    read_trimmed(path)?.parse().ok()
+}
+
 pub struct FileSource {
     path: PathBuf,
     buff: String,
@@ -97,10 +109,15 @@ impl Device {
         &self.name
     }
 
+    pub fn path(&self) -> &Path {
+        &self.base_path
+    }
+
     pub fn sensors(&self) -> impl Iterator<Item = io::Result<Sensor>> {
         // determine early to avoid borrowing &self in iterator
         let is_cpu_thermal = self.name == "cpu_thermal" || self.name == "soc_thermal";
         let is_gpu_thermal = self.name == "gpu_thermal";
+        let is_drivetemp = self.name == "drivetemp";
 
         let sensors = read_dir(&self.base_path).into_iter().flatten();
         sensors
@@ -134,6 +151,13 @@ impl Device {
                         name: "edge".into(),
                     });
                 }
+                // drivetemp only has a single sensor without label, name it the same as the nvme composite sensor
+                if is_drivetemp && input_name == "temp1_input" {
+                    return Ok(Sensor {
+                        input_path: path,
+                        name: "Composite".into(),
+                    });
+                }
 
                 let base_name = input_name.trim_end_matches("_input");
 
@@ -161,4 +185,14 @@ impl Sensor {
     pub fn reader(&self) -> io::Result<FileSource> {
         FileSource::open(&self.input_path)
     }
+
+    /// Read a related attribute of the sensor, like `max` or `crit` for the `temp1_max` attribute of `temp1_input`
+    pub fn attribute<T: FromStr>(&self, attribute: &str) -> Option<T> {
+        let input_name = self.input_path.file_name()?.to_str()?;
+        let base_name = input_name.trim_end_matches("_input");
+        let path = self
+            .input_path
+            .with_file_name(format!("{base_name}_{attribute}"));
+        read_to_string_trimmed(&path).ok()?.parse().ok()
+    }
 }
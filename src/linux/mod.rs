@@ -1,17 +1,44 @@
 pub mod disk;
+pub mod edac;
+pub mod filter;
 pub mod gpu;
 pub mod hwmon;
+pub mod net;
+pub mod numa;
 pub mod power;
+pub mod probe;
 mod proc;
 pub mod sensors;
+pub mod swap;
+pub mod vmstat;
 
+use self::disk::btrfs::btrfs_filesystems;
+use self::disk::info::DiskInfoSource;
+use self::disk::lvm::{lvm_data, update_lvm};
+use self::disk::md::md_arrays;
+use self::disk::smart::{smart_data, update_smart};
+use self::disk::temperature::DriveTemperatureSource;
 use self::disk::zfs::pools;
 use self::disk::*;
+use self::net::bond::bonds;
+use self::net::bridge::bridge_ports;
+use self::net::conntrack::conntrack;
+use self::net::link::LinkSource;
+use self::net::protocol::{protocol_stats, sockstat, tcp_states};
+use self::net::qdisc::QdiscSource;
+use self::net::wireguard::peers;
+use self::net::wireless::{wireless_links, wireless_quality};
+use self::net::{InterfaceFilter, NetworkSource};
 use self::sensors::*;
 use crate::linux::disk::zfs::arcstats;
+use crate::linux::edac::{machine_checks, memory_controllers};
 use crate::linux::gpu::{update_gpu_power, utilization};
+use crate::linux::numa::nodes;
 use crate::linux::power::{CpuPowerSource, GpuPowerSource};
+use crate::linux::probe::{probe_results, start_probes, ProbeConfig};
 use crate::linux::proc::ProcSource;
+use crate::linux::swap::{swaps, zram, zswap};
+use crate::linux::vmstat::{buddyinfo, VmStatSource};
 use crate::{hostname, Error, MultiSensorSource, Result, SensorData, SensorSource};
 use std::fmt::Write;
 use std::sync::Mutex;
@@ -27,10 +54,15 @@ pub struct Sensors {
     pub hostname: String,
     cpu: Mutex<CpuTimeSource>,
     temp: Mutex<TemperatureSource>,
+    drive_temp: Mutex<DriveTemperatureSource>,
     net: Mutex<NetworkSource>,
+    link: Mutex<LinkSource>,
+    qdisc: Mutex<QdiscSource>,
     mem: Mutex<MemorySource>,
+    vmstat: Mutex<VmStatSource>,
     disk_stats: Mutex<DiskStatSource>,
     disk_usage: Mutex<DiskUsageSource>,
+    disk_info: Mutex<DiskInfoSource>,
     cpu_power: Mutex<CpuPowerSource>,
     gpu_power: Mutex<GpuPowerSource>,
     proc: Mutex<ProcSource>,
@@ -39,15 +71,30 @@ pub struct Sensors {
 impl Sensors {
     pub fn new() -> Result<Sensors> {
         std::thread::spawn(update_gpu_power);
+        start_probes(ProbeConfig::from_env()?);
+        let interface_filter = InterfaceFilter::from_env()?;
+        let disk_filter = DiskFilter::from_env()?;
+        if dotenvy::var("ENABLE_SMART").is_ok() {
+            let smart_filter = disk_filter.clone();
+            std::thread::spawn(move || update_smart(smart_filter));
+        }
+        if dotenvy::var("ENABLE_LVM").is_ok() {
+            std::thread::spawn(update_lvm);
+        }
 
         Ok(Sensors {
             hostname: hostname()?,
             cpu: Mutex::new(CpuTimeSource::new()?),
             temp: Mutex::new(TemperatureSource::new()?),
-            net: Mutex::new(NetworkSource::new()?),
+            drive_temp: Mutex::new(DriveTemperatureSource::new()?),
+            net: Mutex::new(NetworkSource::new(interface_filter.clone())?),
+            link: Mutex::new(LinkSource::new(interface_filter.clone())),
+            qdisc: Mutex::new(QdiscSource::new(interface_filter)),
             mem: Mutex::new(MemorySource::new()?),
-            disk_stats: Mutex::new(DiskStatSource::new()?),
-            disk_usage: Mutex::new(DiskUsageSource::new()?),
+            vmstat: Mutex::new(VmStatSource::new()?),
+            disk_stats: Mutex::new(DiskStatSource::new(disk_filter.clone())?),
+            disk_usage: Mutex::new(DiskUsageSource::new(MountFilter::from_env()?)?),
+            disk_info: Mutex::new(DiskInfoSource::new(disk_filter)),
             cpu_power: Mutex::new(CpuPowerSource::new().unwrap_or_default()),
             gpu_power: Mutex::new(GpuPowerSource),
             proc: Mutex::new(ProcSource::new()?),
@@ -63,6 +110,7 @@ pub fn get_metrics(sensors: &Sensors) -> Result<String> {
     let disk_usage = disk_usage_source.read()?;
     let cpu = sensors.cpu.lock().unwrap().read()?;
     let memory = sensors.mem.lock().unwrap().read()?;
+    let vmstat = sensors.vmstat.lock().unwrap().read()?;
     let temperatures = sensors.temp.lock().unwrap().read()?;
     let cpu_power = sensors.cpu_power.lock().unwrap().read()?;
     let gpu_power = sensors.gpu_power.lock().unwrap().read()?;
@@ -74,6 +122,28 @@ pub fn get_metrics(sensors: &Sensors) -> Result<String> {
 
     cpu.write(&mut result, hostname);
     memory.write(&mut result, hostname);
+    vmstat.write(&mut result, hostname);
+    for zone in buddyinfo() {
+        zone.write(&mut result, hostname);
+    }
+    for swap in swaps() {
+        swap.write(&mut result, hostname);
+    }
+    for zram in zram() {
+        zram.write(&mut result, hostname);
+    }
+    if let Some(zswap) = zswap() {
+        zswap.write(&mut result, hostname);
+    }
+    for node in nodes() {
+        node.write(&mut result, hostname);
+    }
+    for controller in memory_controllers() {
+        controller.write(&mut result, hostname);
+    }
+    if let Some(checks) = machine_checks() {
+        checks.write(&mut result, hostname);
+    }
 
     for pool in pools {
         writeln!(
@@ -92,10 +162,62 @@ pub fn get_metrics(sensors: &Sensors) -> Result<String> {
     for network in networks.flatten() {
         network.write(&mut result, hostname);
     }
+    for link in sensors.link.lock().unwrap().read()? {
+        link?.write(&mut result, hostname);
+    }
+    for qdisc in sensors.qdisc.lock().unwrap().read()? {
+        qdisc?.write(&mut result, hostname);
+    }
+    for bond in bonds() {
+        bond.write(&mut result, hostname);
+    }
+    for port in bridge_ports() {
+        port.write(&mut result, hostname);
+    }
+    for stat in protocol_stats() {
+        stat.write(&mut result, hostname);
+    }
+    for stat in sockstat() {
+        stat.write(&mut result, hostname);
+    }
+    tcp_states().write(&mut result, hostname);
+    if let Some(conntrack) = conntrack() {
+        conntrack.write(&mut result, hostname);
+    }
+    for peer in peers() {
+        peer.write(&mut result, hostname);
+    }
+    for quality in wireless_quality() {
+        quality.write(&mut result, hostname);
+    }
+    for link in wireless_links() {
+        link.write(&mut result, hostname);
+    }
+    for probe in probe_results() {
+        probe.write(&mut result, hostname);
+    }
     for disk in disks.flatten() {
         disk.write(&mut result, hostname);
     }
 
+    for disk in sensors.disk_info.lock().unwrap().read()? {
+        disk?.write(&mut result, hostname);
+    }
+    for temperature in sensors.drive_temp.lock().unwrap().read()?.flatten() {
+        temperature.write(&mut result, hostname);
+    }
+    for disk in smart_data() {
+        disk.write(&mut result, hostname);
+    }
+    for array in md_arrays() {
+        array.write(&mut result, hostname);
+    }
+    for filesystem in btrfs_filesystems() {
+        filesystem.write(&mut result, hostname);
+    }
+    for volume in lvm_data() {
+        volume.write(&mut result, hostname);
+    }
     for disk in disk_usage.flatten() {
         disk.write(&mut result, hostname);
     }
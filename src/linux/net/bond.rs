@@ -0,0 +1,132 @@
+use crate::linux::hwmon::read_trimmed;
+use crate::SensorData;
+use std::fmt::Write;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct BondSlave {
+    interface: String,
+    mii_up: bool,
+    link_failures: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Bond {
+    interface: String,
+    mode: String,
+    active_slave: String,
+    mii_up: bool,
+    slaves: Vec<BondSlave>,
+}
+
+impl SensorData for Bond {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"bond_info{{host="{}", bond="{}", mode="{}", active_slave="{}"}} 1"#,
+            hostname, self.interface, self.mode, self.active_slave
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"bond_mii_status{{host="{}", bond="{}"}} {}"#,
+            hostname, self.interface, self.mii_up as u8
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"bond_slaves{{host="{}", bond="{}"}} {}"#,
+            hostname,
+            self.interface,
+            self.slaves.len()
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"bond_slaves_up{{host="{}", bond="{}"}} {}"#,
+            hostname,
+            self.interface,
+            self.slaves.iter().filter(|slave| slave.mii_up).count()
+        )
+        .ok();
+        for slave in &self.slaves {
+            writeln!(
+                &mut w,
+                r#"bond_slave_mii_status{{host="{}", bond="{}", slave="{}"}} {}"#,
+                hostname, self.interface, slave.interface, slave.mii_up as u8
+            )
+            .ok();
+            writeln!(
+                &mut w,
+                r#"bond_slave_link_failures{{host="{}", bond="{}", slave="{}"}} {}"#,
+                hostname, self.interface, slave.interface, slave.link_failures
+            )
+            .ok();
+        }
+    }
+}
+
+/// Status of all bonding interfaces
+///
+/// The bond level details are read from `/sys/class/net/*/bonding`, the slave details from `/proc/net/bonding/*`.
+pub fn bonds() -> Vec<Bond> {
+    read_dir("/sys/class/net")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let bonding = entry.path().join("bonding");
+            if !bonding.is_dir() {
+                return None;
+            }
+            let interface = entry.file_name().into_string().ok()?;
+            // the mode is in the format "active-backup 1"
+            let mode = read_trimmed(bonding.join("mode"))
+                .unwrap_or_default()
+                .split(' ')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let proc =
+                read_to_string(Path::new("/proc/net/bonding").join(&interface)).unwrap_or_default();
+            Some(Bond {
+                mode,
+                active_slave: read_trimmed(bonding.join("active_slave")).unwrap_or_default(),
+                mii_up: read_trimmed(bonding.join("mii_status")).as_deref() == Some("up"),
+                slaves: parse_slaves(&proc),
+                interface,
+            })
+        })
+        .collect()
+}
+
+/// Each slave has its own section starting with the interface name
+///
+/// ```text
+/// Slave Interface: eth0
+/// MII Status: up
+/// Speed: 1000 Mbps
+/// Duplex: full
+/// Link Failure Count: 0
+/// ```
+fn parse_slaves(content: &str) -> Vec<BondSlave> {
+    let mut slaves: Vec<BondSlave> = Vec::new();
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(": ") else {
+            continue;
+        };
+        match (key, slaves.last_mut()) {
+            ("Slave Interface", _) => slaves.push(BondSlave {
+                interface: value.trim().into(),
+                ..BondSlave::default()
+            }),
+            ("MII Status", Some(slave)) => slave.mii_up = value.trim() == "up",
+            ("Link Failure Count", Some(slave)) => {
+                slave.link_failures = value.trim().parse().unwrap_or_default()
+            }
+            _ => {}
+        }
+    }
+    slaves
+}
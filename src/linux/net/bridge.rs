@@ -0,0 +1,72 @@
+use crate::SensorData;
+use std::fmt::Write;
+use std::fs::{read_dir, read_to_string};
+
+/// Port states as used in `/sys/class/net/*/brif/*/state`
+const PORT_STATES: [&str; 5] = [
+    "disabled",
+    "listening",
+    "learning",
+    "forwarding",
+    "blocking",
+];
+
+#[derive(Debug)]
+pub struct BridgePort {
+    bridge: String,
+    port: String,
+    state: u8,
+}
+
+impl SensorData for BridgePort {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        let state = PORT_STATES
+            .get(self.state as usize)
+            .copied()
+            .unwrap_or("unknown");
+        writeln!(
+            &mut w,
+            r#"bridge_port_info{{host="{}", bridge="{}", port="{}", state="{}"}} 1"#,
+            hostname, self.bridge, self.port, state
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"bridge_port_forwarding{{host="{}", bridge="{}", port="{}"}} {}"#,
+            hostname,
+            self.bridge,
+            self.port,
+            (state == "forwarding") as u8
+        )
+        .ok();
+    }
+}
+
+/// The state of all ports of all bridge interfaces
+pub fn bridge_ports() -> Vec<BridgePort> {
+    let mut ports = Vec::new();
+    for bridge in read_dir("/sys/class/net").into_iter().flatten().flatten() {
+        let Ok(bridge_ports) = read_dir(bridge.path().join("brif")) else {
+            continue;
+        };
+        let Ok(bridge_name) = bridge.file_name().into_string() else {
+            continue;
+        };
+        for port in bridge_ports.flatten() {
+            let Some(state) = read_to_string(port.path().join("state"))
+                .ok()
+                .and_then(|state| state.trim().parse().ok())
+            else {
+                continue;
+            };
+            if let Ok(port) = port.file_name().into_string() {
+                ports.push(BridgePort {
+                    bridge: bridge_name.clone(),
+                    port,
+                    state,
+                });
+            }
+        }
+    }
+    ports
+}
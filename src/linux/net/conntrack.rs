@@ -0,0 +1,89 @@
+use crate::linux::hwmon::read_num;
+use crate::SensorData;
+use std::fmt::Write;
+use std::fs::read_to_string;
+
+/// Per-cpu statistics exported from `/proc/net/stat/nf_conntrack`
+const CONNTRACK_STATS: &[&str] = &[
+    "found",
+    "invalid",
+    "insert",
+    "insert_failed",
+    "drop",
+    "early_drop",
+    "icmp_error",
+    "search_restart",
+];
+
+#[derive(Debug, Default)]
+pub struct Conntrack {
+    entries: u64,
+    limit: u64,
+    /// Statistics per cpu
+    stats: Vec<Vec<(&'static str, u64)>>,
+}
+
+impl SensorData for Conntrack {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"conntrack_entries{{host="{}"}} {}"#,
+            hostname, self.entries
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"conntrack_entries_limit{{host="{}"}} {}"#,
+            hostname, self.limit
+        )
+        .ok();
+        for (cpu, stats) in self.stats.iter().enumerate() {
+            for (stat, value) in stats {
+                writeln!(
+                    &mut w,
+                    r#"conntrack_stat{{host="{}", cpu="{}", stat="{}"}} {}"#,
+                    hostname, cpu, stat, value
+                )
+                .ok();
+            }
+        }
+    }
+}
+
+/// Connection tracking usage, only available when the conntrack module is loaded
+pub fn conntrack() -> Option<Conntrack> {
+    let entries = read_num::<u64>("/proc/sys/net/netfilter/nf_conntrack_count")?;
+    let limit = read_num("/proc/sys/net/netfilter/nf_conntrack_max").unwrap_or_default();
+    let stats = read_to_string("/proc/net/stat/nf_conntrack").unwrap_or_default();
+
+    Some(Conntrack {
+        entries,
+        limit,
+        stats: parse_stats(&stats),
+    })
+}
+
+/// The stats file has a header line with the column names, followed by a line of hex values for each cpu
+///
+/// The available columns differ between kernel versions.
+fn parse_stats(content: &str) -> Vec<Vec<(&'static str, u64)>> {
+    let mut lines = content.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let columns: Vec<_> = header
+        .split_ascii_whitespace()
+        .map(|column| CONNTRACK_STATS.iter().find(|stat| **stat == column))
+        .collect();
+
+    lines
+        .map(|line| {
+            line.split_ascii_whitespace()
+                .zip(columns.iter())
+                .filter_map(|(value, column)| {
+                    Some((*(*column)?, u64::from_str_radix(value, 16).ok()?))
+                })
+                .collect()
+        })
+        .collect()
+}
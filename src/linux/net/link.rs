@@ -0,0 +1,124 @@
+use crate::linux::hwmon::{read_num, read_trimmed};
+use crate::linux::net::InterfaceFilter;
+use crate::{MultiSensorSource, Result, SensorData};
+use std::fmt::Write;
+use std::fs::{read_dir, read_link};
+use std::path::Path;
+use std::vec::IntoIter;
+
+#[derive(Debug, Default)]
+pub struct LinkInfo {
+    interface: String,
+    operstate: String,
+    duplex: String,
+    address: String,
+    driver: String,
+    carrier: Option<bool>,
+    carrier_changes: Option<u64>,
+    /// Link speed in Mbit/s
+    speed: Option<u64>,
+    mtu: Option<u64>,
+}
+
+impl SensorData for LinkInfo {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"net_info{{host="{}", network="{}", operstate="{}", duplex="{}", address="{}", driver="{}"}} 1"#,
+            hostname, self.interface, self.operstate, self.duplex, self.address, self.driver
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"net_up{{host="{}", network="{}"}} {}"#,
+            hostname,
+            self.interface,
+            (self.operstate == "up") as u8
+        )
+        .ok();
+        if let Some(carrier) = self.carrier {
+            writeln!(
+                &mut w,
+                r#"net_carrier{{host="{}", network="{}"}} {}"#,
+                hostname, self.interface, carrier as u8
+            )
+            .ok();
+        }
+        if let Some(carrier_changes) = self.carrier_changes {
+            writeln!(
+                &mut w,
+                r#"net_carrier_changes{{host="{}", network="{}"}} {}"#,
+                hostname, self.interface, carrier_changes
+            )
+            .ok();
+        }
+        if let Some(speed) = self.speed {
+            // in bytes per second to match the net_sent and net_received counters
+            writeln!(
+                &mut w,
+                r#"net_speed{{host="{}", network="{}"}} {}"#,
+                hostname,
+                self.interface,
+                speed * 125_000
+            )
+            .ok();
+        }
+        if let Some(mtu) = self.mtu {
+            writeln!(
+                &mut w,
+                r#"net_mtu{{host="{}", network="{}"}} {}"#,
+                hostname, self.interface, mtu
+            )
+            .ok();
+        }
+    }
+}
+
+pub struct LinkSource {
+    filter: InterfaceFilter,
+}
+
+impl LinkSource {
+    pub fn new(filter: InterfaceFilter) -> LinkSource {
+        LinkSource { filter }
+    }
+}
+
+impl MultiSensorSource for LinkSource {
+    type Data = LinkInfo;
+    type Iter<'a> = IntoIter<Result<LinkInfo>>;
+
+    fn read(&mut self) -> Result<Self::Iter<'_>> {
+        Ok(read_dir("/sys/class/net")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let interface = entry.file_name().into_string().ok()?;
+                self.filter
+                    .matches(&interface)
+                    .then(|| Ok(link_info(interface, &entry.path())))
+            })
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+}
+
+fn link_info(interface: String, path: &Path) -> LinkInfo {
+    // carrier and speed can't be read while the interface is down
+    LinkInfo {
+        operstate: read_trimmed(path.join("operstate")).unwrap_or_default(),
+        duplex: read_trimmed(path.join("duplex")).unwrap_or_default(),
+        address: read_trimmed(path.join("address")).unwrap_or_default(),
+        driver: read_link(path.join("device/driver"))
+            .ok()
+            .and_then(|driver| Some(driver.file_name()?.to_str()?.to_string()))
+            .unwrap_or_default(),
+        carrier: read_num::<u8>(path.join("carrier")).map(|carrier| carrier == 1),
+        carrier_changes: read_num(path.join("carrier_changes")),
+        // speed is reported as -1 when unknown, which fails to parse as unsigned
+        speed: read_num(path.join("speed")),
+        mtu: read_num(path.join("mtu")),
+        interface,
+    }
+}
@@ -0,0 +1,138 @@
+use crate::data::NetStats;
+use crate::linux::filter::NameFilter;
+use crate::{Error, IoResultExt, MultiSensorSource, Result};
+use std::fs::File;
+use std::io::{ErrorKind, Read, Seek};
+use std::path::Path;
+
+pub mod bond;
+pub mod bridge;
+pub mod conntrack;
+pub mod link;
+mod netlink;
+#[cfg(test)]
+mod netns;
+pub mod protocol;
+pub mod qdisc;
+pub mod wireguard;
+pub mod wireless;
+
+/// Selects the network interfaces to report
+#[derive(Debug, Clone)]
+pub struct InterfaceFilter {
+    names: NameFilter,
+    physical_only: bool,
+}
+
+impl InterfaceFilter {
+    /// Configured with `NETWORK_INCLUDE`, `NETWORK_EXCLUDE` and `NETWORK_PHYSICAL_ONLY`
+    ///
+    /// By default only `en*`, `eth*` and `wlp*` interfaces are included, unless only physical interfaces are selected.
+    pub fn from_env() -> Result<InterfaceFilter> {
+        let physical_only = dotenvy::var("NETWORK_PHYSICAL_ONLY").is_ok();
+        let default_include = (!physical_only).then_some("^(en|eth|wlp)");
+        Ok(InterfaceFilter {
+            names: NameFilter::from_env("NETWORK", default_include, None)?,
+            physical_only,
+        })
+    }
+
+    pub fn matches(&self, interface: &str) -> bool {
+        self.names.matches(interface) && (!self.physical_only || is_physical(interface))
+    }
+}
+
+/// Physical interfaces are backed by a device, virtual interfaces like bridges and tunnels are not
+fn is_physical(interface: &str) -> bool {
+    Path::new("/sys/class/net")
+        .join(interface)
+        .join("device")
+        .exists()
+}
+
+pub struct NetworkSource {
+    source: File,
+    buff: String,
+    filter: InterfaceFilter,
+}
+
+impl NetworkSource {
+    pub fn new(filter: InterfaceFilter) -> Result<NetworkSource> {
+        Ok(NetworkSource {
+            source: File::open("/proc/net/dev").context("error opening netdev")?,
+            buff: String::new(),
+            filter,
+        })
+    }
+
+    fn parse_line(line: &str) -> Result<NetStats> {
+        let invalid = || Error::io("error reading netdev", ErrorKind::InvalidData.into());
+
+        // the interface name isn't always separated by whitespace from the first value
+        let (interface, counters) = line.trim_start().split_once(':').ok_or_else(invalid)?;
+        let mut parts = counters.split_ascii_whitespace();
+        let mut next = || -> Result<u64> { Ok(parts.next().ok_or_else(invalid)?.parse()?) };
+
+        // fields are evaluated in order, matching the column order in netdev
+        Ok(NetStats {
+            interface: interface.into(),
+            bytes_received: next()?,
+            packets_received: next()?,
+            errors_received: next()?,
+            drops_received: next()?,
+            fifo_errors_received: next()?,
+            frame_errors_received: next()?,
+            compressed_received: next()?,
+            multicast_received: next()?,
+            bytes_sent: next()?,
+            packets_sent: next()?,
+            errors_sent: next()?,
+            drops_sent: next()?,
+            fifo_errors_sent: next()?,
+            collisions: next()?,
+            carrier_errors: next()?,
+            compressed_sent: next()?,
+        })
+    }
+}
+
+impl MultiSensorSource for NetworkSource {
+    type Data = NetStats;
+    type Iter<'a> = NetworkStatParser<'a>;
+
+    fn read(&mut self) -> Result<Self::Iter<'_>> {
+        self.buff.clear();
+        self.source.rewind().context("error rewinding netdev")?;
+        self.source
+            .read_to_string(&mut self.buff)
+            .context("error reading netdev")?;
+
+        Ok(NetworkStatParser {
+            lines: self.buff.lines(),
+            filter: &self.filter,
+        })
+    }
+}
+
+pub struct NetworkStatParser<'a> {
+    lines: std::str::Lines<'a>,
+    filter: &'a InterfaceFilter,
+}
+
+impl<'a> Iterator for NetworkStatParser<'a> {
+    type Item = Result<NetStats>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = loop {
+            let line = self.lines.next()?;
+            let trimmed = line.trim_start();
+            if let Some((interface, _)) = trimmed.split_once(':') {
+                if self.filter.matches(interface) {
+                    break trimmed;
+                }
+            }
+        };
+
+        Some(NetworkSource::parse_line(line))
+    }
+}
@@ -0,0 +1,289 @@
+//! Minimal netlink client for querying kernel network state
+//!
+//! Only supports the request/response flow needed for reading statistics: a single request
+//! followed by one or more (multipart) responses.
+
+use std::io;
+use std::mem::size_of;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::time::Duration;
+
+const HEADER_LEN: usize = 16;
+const GENL_HEADER_LEN: usize = 4;
+const ATTR_HEADER_LEN: usize = 4;
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+pub struct NetlinkSocket {
+    fd: OwnedFd,
+    seq: u32,
+    buff: Vec<u8>,
+}
+
+impl NetlinkSocket {
+    pub fn open(protocol: libc::c_int) -> io::Result<NetlinkSocket> {
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                protocol,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        let result = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // don't let a misbehaving kernel module block the scrape
+        let timeout = Duration::from_secs(2);
+        let timeout = libc::timeval {
+            tv_sec: timeout.as_secs() as libc::time_t,
+            tv_usec: 0,
+        };
+        let result = unsafe {
+            libc::setsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &timeout as *const libc::timeval as *const libc::c_void,
+                size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(NetlinkSocket {
+            fd,
+            seq: 0,
+            buff: vec![0; 64 * 1024],
+        })
+    }
+
+    /// Send a request and collect the payloads of all response messages
+    ///
+    /// Dump requests are answered with a multipart response terminated by `NLMSG_DONE`,
+    /// other requests are answered with a single response or acknowledgement.
+    pub fn request(
+        &mut self,
+        message_type: u16,
+        flags: u16,
+        payload: &[u8],
+    ) -> io::Result<Vec<Vec<u8>>> {
+        self.seq = self.seq.wrapping_add(1);
+        let dump = flags & libc::NLM_F_DUMP as u16 == libc::NLM_F_DUMP as u16;
+        // request an acknowledgement so we know when a non-dump response is complete
+        let flags = if dump {
+            flags | libc::NLM_F_REQUEST as u16
+        } else {
+            flags | libc::NLM_F_REQUEST as u16 | libc::NLM_F_ACK as u16
+        };
+
+        let mut message = Vec::with_capacity(HEADER_LEN + payload.len());
+        message.extend_from_slice(&((HEADER_LEN + payload.len()) as u32).to_ne_bytes());
+        message.extend_from_slice(&message_type.to_ne_bytes());
+        message.extend_from_slice(&flags.to_ne_bytes());
+        message.extend_from_slice(&self.seq.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes());
+        message.extend_from_slice(payload);
+
+        let sent = unsafe {
+            libc::send(
+                self.fd.as_raw_fd(),
+                message.as_ptr() as *const libc::c_void,
+                message.len(),
+                0,
+            )
+        };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut responses = Vec::new();
+        loop {
+            let received = unsafe {
+                libc::recv(
+                    self.fd.as_raw_fd(),
+                    self.buff.as_mut_ptr() as *mut libc::c_void,
+                    self.buff.len(),
+                    0,
+                )
+            };
+            if received < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut data = &self.buff[..received as usize];
+
+            while data.len() >= HEADER_LEN {
+                let invalid = || io::Error::from(io::ErrorKind::InvalidData);
+                let len = read_u32(data, 0).ok_or_else(invalid)? as usize;
+                let kind = read_u16(data, 4).ok_or_else(invalid)?;
+                let seq = read_u32(data, 8).ok_or_else(invalid)?;
+                if len < HEADER_LEN || len > data.len() {
+                    return Err(invalid());
+                }
+                let body = &data[HEADER_LEN..len];
+                data = &data[align(len).min(data.len())..];
+
+                if seq != self.seq {
+                    continue;
+                }
+                match kind as libc::c_int {
+                    libc::NLMSG_NOOP => {}
+                    libc::NLMSG_DONE => return Ok(responses),
+                    libc::NLMSG_ERROR => {
+                        let error = read_u32(body, 0).ok_or_else(invalid)? as i32;
+                        if error != 0 {
+                            return Err(io::Error::from_raw_os_error(-error));
+                        }
+                        // acknowledgement, the final message for non-dump requests
+                        return Ok(responses);
+                    }
+                    _ => responses.push(body.to_vec()),
+                }
+            }
+        }
+    }
+}
+
+/// Append a netlink attribute to a message payload
+pub fn push_attribute(message: &mut Vec<u8>, kind: u16, data: &[u8]) {
+    let len = ATTR_HEADER_LEN + data.len();
+    message.extend_from_slice(&(len as u16).to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(data);
+    message.resize(message.len() + align(len) - len, 0);
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Attribute<'a> {
+    pub kind: u16,
+    pub data: &'a [u8],
+}
+
+impl<'a> Attribute<'a> {
+    pub fn u8(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    pub fn u16(&self) -> Option<u16> {
+        read_u16(self.data, 0)
+    }
+
+    pub fn u32(&self) -> Option<u32> {
+        read_u32(self.data, 0)
+    }
+
+    pub fn u64(&self) -> Option<u64> {
+        Some(u64::from_ne_bytes(self.data.get(0..8)?.try_into().ok()?))
+    }
+
+    pub fn nested(&self) -> Attributes<'a> {
+        Attributes::new(self.data)
+    }
+}
+
+/// Iterator over the netlink attributes in a message payload
+#[derive(Debug, Clone)]
+pub struct Attributes<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Attributes<'a> {
+    pub fn new(data: &'a [u8]) -> Attributes<'a> {
+        Attributes { data }
+    }
+}
+
+impl<'a> Iterator for Attributes<'a> {
+    type Item = Attribute<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = read_u16(self.data, 0)? as usize;
+        let kind = read_u16(self.data, 2)? & libc::NLA_TYPE_MASK as u16;
+        if len < ATTR_HEADER_LEN || len > self.data.len() {
+            self.data = &[];
+            return None;
+        }
+        let data = &self.data[ATTR_HEADER_LEN..len];
+        self.data = &self.data[align(len).min(self.data.len())..];
+        Some(Attribute { kind, data })
+    }
+}
+
+/// Client for a generic netlink family
+pub struct GenericNetlink {
+    socket: NetlinkSocket,
+    family: u16,
+    version: u8,
+}
+
+impl GenericNetlink {
+    pub fn open(family_name: &str, version: u8) -> io::Result<GenericNetlink> {
+        let mut socket = NetlinkSocket::open(libc::NETLINK_GENERIC)?;
+
+        let mut name = family_name.as_bytes().to_vec();
+        name.push(0);
+        let mut payload = vec![libc::CTRL_CMD_GETFAMILY as u8, 1, 0, 0];
+        push_attribute(&mut payload, libc::CTRL_ATTR_FAMILY_NAME as u16, &name);
+
+        let responses = socket.request(libc::GENL_ID_CTRL as u16, 0, &payload)?;
+        let family = responses
+            .iter()
+            .flat_map(|response| {
+                Attributes::new(response.get(GENL_HEADER_LEN..).unwrap_or_default())
+            })
+            .find(|attribute| attribute.kind == libc::CTRL_ATTR_FAMILY_ID as u16)
+            .and_then(|attribute| attribute.u16())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+
+        Ok(GenericNetlink {
+            socket,
+            family,
+            version,
+        })
+    }
+
+    /// Send a command and return the attribute payloads of the responses
+    pub fn request(
+        &mut self,
+        command: u8,
+        flags: u16,
+        attributes: &[u8],
+    ) -> io::Result<Vec<Vec<u8>>> {
+        let mut payload = vec![command, self.version, 0, 0];
+        payload.extend_from_slice(attributes);
+        let mut responses = self.socket.request(self.family, flags, &payload)?;
+        for response in responses.iter_mut() {
+            response.drain(..GENL_HEADER_LEN.min(response.len()));
+        }
+        Ok(responses)
+    }
+}
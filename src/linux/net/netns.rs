@@ -0,0 +1,63 @@
+//! Helper for running tests against real network devices in a throw-away network namespace
+//!
+//! The test binary is re-executed inside the namespace with `ip netns exec`, which also mounts
+//! a sysfs for the namespace so `/sys/class/net` only lists its devices.
+//! This requires root and iproute2, so these tests are ignored by default.
+
+use std::env::current_exe;
+use std::process::Command;
+
+const INSIDE_VAR: &str = "PALANTIR_TEST_NETNS";
+
+struct Namespace(String);
+
+impl Drop for Namespace {
+    fn drop(&mut self) {
+        Command::new("ip")
+            .args(["netns", "del", &self.0])
+            .status()
+            .ok();
+    }
+}
+
+/// Returns `true` when running inside the namespace, at which point the test should run its checks
+///
+/// Otherwise a namespace is created, the `setup` shell commands are executed in it and the test
+/// with the full path `test` is re-executed inside it, failing if the inner run fails.
+pub fn in_netns(test: &str, setup: &[&str]) -> bool {
+    if std::env::var_os(INSIDE_VAR).is_some() {
+        return true;
+    }
+
+    let name = format!("palantir-test-{}", std::process::id());
+    let status = Command::new("ip")
+        .args(["netns", "add", &name])
+        .status()
+        .expect("failed to run ip");
+    assert!(status.success(), "failed to create network namespace");
+    let namespace = Namespace(name);
+
+    for command in setup {
+        let status = Command::new("ip")
+            .args(["netns", "exec", &namespace.0, "sh", "-c", command])
+            .status()
+            .unwrap();
+        assert!(status.success(), "setup command failed: {command}");
+    }
+
+    let status = Command::new("ip")
+        .args(["netns", "exec", &namespace.0])
+        .arg(current_exe().unwrap())
+        .args([
+            "--exact",
+            test,
+            "--ignored",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env(INSIDE_VAR, "1")
+        .status()
+        .unwrap();
+    assert!(status.success(), "test failed inside the network namespace");
+    false
+}
@@ -0,0 +1,211 @@
+use crate::SensorData;
+use std::fmt::Write;
+use std::fs::{read_to_string, File};
+use std::io::{BufRead, BufReader};
+
+/// Counters exported from `/proc/net/snmp` and `/proc/net/netstat`, grouped by protocol
+const PROTOCOL_STATS: &[(&str, &[&str])] = &[
+    (
+        "Tcp",
+        &[
+            "ActiveOpens",
+            "PassiveOpens",
+            "AttemptFails",
+            "EstabResets",
+            "CurrEstab",
+            "InSegs",
+            "OutSegs",
+            "RetransSegs",
+            "InErrs",
+            "OutRsts",
+            "InCsumErrors",
+        ],
+    ),
+    (
+        "Udp",
+        &[
+            "InDatagrams",
+            "NoPorts",
+            "InErrors",
+            "OutDatagrams",
+            "RcvbufErrors",
+            "SndbufErrors",
+            "InCsumErrors",
+        ],
+    ),
+    (
+        "TcpExt",
+        &[
+            "ListenOverflows",
+            "ListenDrops",
+            "TCPTimeouts",
+            "TCPSynRetrans",
+            "TCPLostRetransmit",
+            "TCPAbortOnTimeout",
+            "TCPAbortOnMemory",
+            "TCPBacklogDrop",
+            "TCPRcvQDrop",
+            "SyncookiesSent",
+            "SyncookiesRecv",
+            "SyncookiesFailed",
+        ],
+    ),
+];
+
+#[derive(Debug)]
+pub struct ProtocolStat {
+    protocol: &'static str,
+    stat: &'static str,
+    value: u64,
+}
+
+impl SensorData for ProtocolStat {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"netstat{{host="{}", protocol="{}", stat="{}"}} {}"#,
+            hostname, self.protocol, self.stat, self.value
+        )
+        .ok();
+    }
+}
+
+pub fn protocol_stats() -> Vec<ProtocolStat> {
+    let mut stats = Vec::new();
+    for path in ["/proc/net/snmp", "/proc/net/netstat"] {
+        let content = read_to_string(path).unwrap_or_default();
+        parse_protocol_stats(&content, &mut stats);
+    }
+    stats
+}
+
+/// Both files consist of pairs of lines, a header line with the field names followed by a line with the values
+///
+/// ```text
+/// Tcp: RtoAlgorithm RtoMin RtoMax MaxConn ActiveOpens ...
+/// Tcp: 1 200 120000 -1 245 ...
+/// ```
+fn parse_protocol_stats(content: &str, stats: &mut Vec<ProtocolStat>) {
+    let mut lines = content.lines();
+    while let (Some(header), Some(values)) = (lines.next(), lines.next()) {
+        let (Some((protocol, names)), Some((_, values))) =
+            (header.split_once(": "), values.split_once(": "))
+        else {
+            continue;
+        };
+        let Some((protocol, fields)) = PROTOCOL_STATS.iter().find(|(name, _)| *name == protocol)
+        else {
+            continue;
+        };
+        for (name, value) in names.split(' ').zip(values.split(' ')) {
+            if let (Some(stat), Ok(value)) =
+                (fields.iter().find(|field| **field == name), value.parse())
+            {
+                stats.push(ProtocolStat {
+                    protocol,
+                    stat,
+                    value,
+                });
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SocketStat {
+    protocol: String,
+    stat: String,
+    value: u64,
+}
+
+impl SensorData for SocketStat {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"sockstat{{host="{}", protocol="{}", stat="{}"}} {}"#,
+            hostname, self.protocol, self.stat, self.value
+        )
+        .ok();
+    }
+}
+
+/// Socket usage per protocol from `/proc/net/sockstat` and `/proc/net/sockstat6`
+///
+/// Lines are in the format `TCP: inuse 4 orphan 0 tw 2 alloc 4 mem 0`
+pub fn sockstat() -> Vec<SocketStat> {
+    let mut stats = Vec::new();
+    for path in ["/proc/net/sockstat", "/proc/net/sockstat6"] {
+        let content = read_to_string(path).unwrap_or_default();
+        for line in content.lines() {
+            let Some((protocol, values)) = line.split_once(": ") else {
+                continue;
+            };
+            let mut parts = values.split_ascii_whitespace();
+            while let (Some(stat), Some(Ok(value))) = (parts.next(), parts.next().map(str::parse)) {
+                stats.push(SocketStat {
+                    protocol: protocol.into(),
+                    stat: stat.into(),
+                    value,
+                });
+            }
+        }
+    }
+    stats
+}
+
+const TCP_STATES: [&str; 12] = [
+    "established",
+    "syn_sent",
+    "syn_recv",
+    "fin_wait1",
+    "fin_wait2",
+    "time_wait",
+    "close",
+    "close_wait",
+    "last_ack",
+    "listen",
+    "closing",
+    "new_syn_recv",
+];
+
+#[derive(Debug, Default)]
+pub struct TcpStates {
+    counts: [u64; TCP_STATES.len()],
+}
+
+impl SensorData for TcpStates {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        for (state, count) in TCP_STATES.iter().zip(self.counts) {
+            writeln!(
+                &mut w,
+                r#"tcp_connections{{host="{}", state="{}"}} {}"#,
+                hostname, state, count
+            )
+            .ok();
+        }
+    }
+}
+
+/// Count the ipv4 and ipv6 tcp sockets in each state
+pub fn tcp_states() -> TcpStates {
+    let mut states = TcpStates::default();
+    for path in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(file) = File::open(path) else {
+            continue;
+        };
+        // the socket tables can get large, so we avoid reading them into memory at once
+        for line in BufReader::new(file).lines().skip(1).map_while(Result::ok) {
+            let state = line
+                .split_ascii_whitespace()
+                .nth(3)
+                .and_then(|state| u8::from_str_radix(state, 16).ok());
+            if let Some(count) = state
+                .and_then(|state| state.checked_sub(1))
+                .and_then(|index| states.counts.get_mut(index as usize))
+            {
+                *count += 1;
+            }
+        }
+    }
+    states
+}
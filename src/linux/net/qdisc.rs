@@ -0,0 +1,316 @@
+use crate::linux::net::netlink::{Attributes, NetlinkSocket};
+use crate::linux::net::InterfaceFilter;
+use crate::{MultiSensorSource, Result, SensorData};
+use std::ffi::CStr;
+use std::fmt::Write;
+use std::vec::IntoIter;
+use tracing::warn;
+
+// from linux/rtnetlink.h and linux/gen_stats.h
+const TCMSG_LEN: usize = 20;
+const TCA_KIND: u16 = 1;
+const TCA_STATS2: u16 = 7;
+const TCA_STATS_BASIC: u16 = 1;
+const TCA_STATS_QUEUE: u16 = 3;
+const TCA_STATS_PKT64: u16 = 8;
+const TC_H_ROOT: u32 = 0xFFFF_FFFF;
+const TC_H_INGRESS: u32 = 0xFFFF_FFF1;
+
+#[derive(Debug, Default)]
+pub struct QdiscStats {
+    interface: String,
+    kind: String,
+    handle: u32,
+    parent: u32,
+    bytes: u64,
+    packets: u64,
+    drops: u32,
+    overlimits: u32,
+    requeues: u32,
+    /// Queue length in bytes
+    backlog: u32,
+    /// Queue length in packets
+    qlen: u32,
+}
+
+/// Format a handle the same way `tc` does, `major:minor` in hex, with the minor omitted when 0
+fn format_handle(handle: u32) -> String {
+    match handle {
+        TC_H_ROOT => "root".into(),
+        TC_H_INGRESS => "ingress".into(),
+        handle if handle & 0xFFFF == 0 => format!("{:x}:", handle >> 16),
+        handle => format!("{:x}:{:x}", handle >> 16, handle & 0xFFFF),
+    }
+}
+
+impl SensorData for QdiscStats {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        let handle = format_handle(self.handle);
+        let parent = format_handle(self.parent);
+        let values = [
+            ("bytes", self.bytes),
+            ("packets", self.packets),
+            ("drops", self.drops as u64),
+            ("overlimits", self.overlimits as u64),
+            ("requeues", self.requeues as u64),
+            ("backlog", self.backlog as u64),
+            ("qlen", self.qlen as u64),
+        ];
+        for (name, value) in values {
+            writeln!(
+                &mut w,
+                r#"qdisc_{}{{host="{}", network="{}", kind="{}", handle="{}", parent="{}"}} {}"#,
+                name, hostname, self.interface, self.kind, handle, parent, value
+            )
+            .ok();
+        }
+    }
+}
+
+pub struct QdiscSource {
+    filter: InterfaceFilter,
+    socket: Option<NetlinkSocket>,
+}
+
+impl QdiscSource {
+    pub fn new(filter: InterfaceFilter) -> QdiscSource {
+        QdiscSource {
+            filter,
+            socket: None,
+        }
+    }
+
+    fn query(&mut self) -> std::io::Result<Vec<Vec<u8>>> {
+        let socket = match &mut self.socket {
+            Some(socket) => socket,
+            socket => socket.insert(NetlinkSocket::open(libc::NETLINK_ROUTE)?),
+        };
+        // an empty tcmsg to dump the qdiscs of all interfaces
+        let request = [0; TCMSG_LEN];
+        socket.request(libc::RTM_GETQDISC, libc::NLM_F_DUMP as u16, &request)
+    }
+}
+
+impl MultiSensorSource for QdiscSource {
+    type Data = QdiscStats;
+    type Iter<'a> = IntoIter<Result<QdiscStats>>;
+
+    fn read(&mut self) -> Result<Self::Iter<'_>> {
+        let responses = match self.query() {
+            Ok(responses) => responses,
+            Err(e) => {
+                warn!(error = display(e), "Failed to query qdisc stats");
+                // reopen the socket on the next scrape in case it's in a bad state
+                self.socket = None;
+                Vec::new()
+            }
+        };
+        Ok(responses
+            .iter()
+            .filter_map(|response| parse_qdisc(response))
+            .filter(|qdisc| self.filter.matches(&qdisc.interface))
+            .map(Ok)
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+}
+
+fn interface_name(index: u32) -> Option<String> {
+    let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+    let result = unsafe { libc::if_indextoname(index, name.as_mut_ptr()) };
+    if result.is_null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(name.as_ptr()) };
+    name.to_str().ok().map(String::from)
+}
+
+/// A `struct tcmsg` followed by the qdisc attributes
+fn parse_qdisc(message: &[u8]) -> Option<QdiscStats> {
+    let read_u32 = |offset: usize| -> Option<u32> {
+        Some(u32::from_ne_bytes(
+            message.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    let mut qdisc = QdiscStats {
+        interface: interface_name(read_u32(4)?)?,
+        handle: read_u32(8)?,
+        parent: read_u32(12)?,
+        ..QdiscStats::default()
+    };
+
+    for attribute in Attributes::new(message.get(TCMSG_LEN..)?) {
+        match attribute.kind {
+            TCA_KIND => {
+                qdisc.kind = CStr::from_bytes_until_nul(attribute.data)
+                    .ok()?
+                    .to_string_lossy()
+                    .into()
+            }
+            TCA_STATS2 => {
+                for stat in attribute.nested() {
+                    let field = |index: usize| -> u32 {
+                        stat.data
+                            .get(index * 4..index * 4 + 4)
+                            .and_then(|bytes| bytes.try_into().ok())
+                            .map(u32::from_ne_bytes)
+                            .unwrap_or_default()
+                    };
+                    match stat.kind {
+                        // struct gnet_stats_basic { __u64 bytes; __u32 packets; }
+                        TCA_STATS_BASIC => {
+                            qdisc.bytes = stat.u64().unwrap_or_default();
+                            qdisc.packets = qdisc.packets.max(field(2) as u64);
+                        }
+                        // the packet counter in gnet_stats_basic is only 32 bit
+                        TCA_STATS_PKT64 => qdisc.packets = stat.u64().unwrap_or_default(),
+                        // struct gnet_stats_queue { __u32 qlen, backlog, drops, requeues, overlimits; }
+                        TCA_STATS_QUEUE => {
+                            qdisc.qlen = field(0);
+                            qdisc.backlog = field(1);
+                            qdisc.drops = field(2);
+                            qdisc.requeues = field(3);
+                            qdisc.overlimits = field(4);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(qdisc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linux::net::netlink::push_attribute;
+    use crate::linux::net::netns::in_netns;
+
+    /// A `struct tcmsg` for the loopback interface followed by the kind and stats attributes
+    fn message(handle: u32, parent: u32, kind: &str, stats: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut message = vec![0; TCMSG_LEN];
+        message[0] = libc::AF_UNSPEC as u8;
+        message[4..8].copy_from_slice(&1i32.to_ne_bytes());
+        message[8..12].copy_from_slice(&handle.to_ne_bytes());
+        message[12..16].copy_from_slice(&parent.to_ne_bytes());
+        push_attribute(&mut message, TCA_KIND, format!("{kind}\0").as_bytes());
+        let mut nested = Vec::new();
+        for (kind, data) in stats {
+            push_attribute(&mut nested, *kind, data);
+        }
+        push_attribute(&mut message, TCA_STATS2, &nested);
+        message
+    }
+
+    fn basic(bytes: u64, packets: u32) -> (u16, Vec<u8>) {
+        let mut data = bytes.to_ne_bytes().to_vec();
+        data.extend_from_slice(&packets.to_ne_bytes());
+        (TCA_STATS_BASIC, data)
+    }
+
+    fn queue(
+        qlen: u32,
+        backlog: u32,
+        drops: u32,
+        requeues: u32,
+        overlimits: u32,
+    ) -> (u16, Vec<u8>) {
+        let data = [qlen, backlog, drops, requeues, overlimits]
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        (TCA_STATS_QUEUE, data)
+    }
+
+    #[test]
+    fn test_format_handle() {
+        assert_eq!(format_handle(TC_H_ROOT), "root");
+        assert_eq!(format_handle(TC_H_INGRESS), "ingress");
+        assert_eq!(format_handle(0x8001_0000), "8001:");
+        assert_eq!(format_handle(0x0001_000a), "1:a");
+        assert_eq!(format_handle(0), "0:");
+    }
+
+    #[test]
+    fn test_parse_basic_and_queue() {
+        let message = message(
+            0x8001_0000,
+            TC_H_ROOT,
+            "fq_codel",
+            &[basic(123456, 789), queue(1, 1514, 3, 4, 5)],
+        );
+        let qdisc = parse_qdisc(&message).unwrap();
+        assert_eq!(qdisc.interface, "lo");
+        assert_eq!(qdisc.kind, "fq_codel");
+        assert_eq!(qdisc.handle, 0x8001_0000);
+        assert_eq!(qdisc.parent, TC_H_ROOT);
+        assert_eq!(qdisc.bytes, 123456);
+        assert_eq!(qdisc.packets, 789);
+        assert_eq!(qdisc.qlen, 1);
+        assert_eq!(qdisc.backlog, 1514);
+        assert_eq!(qdisc.drops, 3);
+        assert_eq!(qdisc.requeues, 4);
+        assert_eq!(qdisc.overlimits, 5);
+    }
+
+    #[test]
+    fn test_parse_pkt64() {
+        let packets = u32::MAX as u64 + 10;
+        // the kernel sends the 64 bit counter after the basic stats with the truncated counter
+        let pkt64 = (TCA_STATS_PKT64, packets.to_ne_bytes().to_vec());
+        let message = message(0x0001_0000, TC_H_ROOT, "htb", &[basic(1, 9), pkt64]);
+        assert_eq!(parse_qdisc(&message).unwrap().packets, packets);
+    }
+
+    #[test]
+    fn test_parse_truncated() {
+        let message = message(0x0001_0000, TC_H_ROOT, "htb", &[]);
+        assert!(parse_qdisc(&message[..12]).is_none());
+        let qdisc = parse_qdisc(&message[..TCMSG_LEN]).unwrap();
+        assert_eq!(qdisc.kind, "");
+        assert_eq!(qdisc.bytes, 0);
+    }
+
+    #[test]
+    fn test_write() {
+        let message = message(0x0001_0000, TC_H_INGRESS, "htb", &[basic(10, 2)]);
+        let mut out = String::new();
+        parse_qdisc(&message).unwrap().write(&mut out, "host");
+        assert!(out.contains(
+            r#"qdisc_bytes{host="host", network="lo", kind="htb", handle="1:", parent="ingress"} 10"#
+        ));
+    }
+
+    /// Requires root and `tc` from iproute2
+    #[test]
+    #[ignore = "requires root and iproute2"]
+    fn test_netns_qdiscs() {
+        let setup = [
+            "ip link add eth0 type veth peer name eth1",
+            "ip link set eth0 up",
+            "ip link set eth1 up",
+            "tc qdisc replace dev eth0 root handle 1: htb",
+            "tc qdisc add dev eth0 ingress",
+        ];
+        if !in_netns("linux::net::qdisc::tests::test_netns_qdiscs", &setup) {
+            return;
+        }
+
+        let mut source = QdiscSource::new(InterfaceFilter::from_env().unwrap());
+        let qdiscs = source.read().unwrap().flatten().collect::<Vec<_>>();
+        assert!(qdiscs.iter().all(|qdisc| qdisc.interface != "lo"));
+        let root = qdiscs
+            .iter()
+            .find(|qdisc| qdisc.interface == "eth0" && qdisc.parent == TC_H_ROOT)
+            .unwrap();
+        assert_eq!(root.kind, "htb");
+        assert_eq!(format_handle(root.handle), "1:");
+        let ingress = qdiscs
+            .iter()
+            .find(|qdisc| qdisc.interface == "eth0" && qdisc.kind == "ingress")
+            .unwrap();
+        assert_eq!(format_handle(ingress.parent), "ingress");
+    }
+}
@@ -0,0 +1,466 @@
+use crate::linux::net::netlink::{push_attribute, Attribute, Attributes, GenericNetlink};
+use crate::SensorData;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use std::fmt::Write;
+use std::fs::{read_dir, read_to_string};
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::warn;
+
+// from linux/wireguard.h
+const WG_CMD_GET_DEVICE: u8 = 0;
+const WGDEVICE_A_IFNAME: u16 = 2;
+const WGDEVICE_A_PEERS: u16 = 8;
+const WGPEER_A_PUBLIC_KEY: u16 = 1;
+const WGPEER_A_ENDPOINT: u16 = 4;
+const WGPEER_A_LAST_HANDSHAKE_TIME: u16 = 6;
+const WGPEER_A_RX_BYTES: u16 = 7;
+const WGPEER_A_TX_BYTES: u16 = 8;
+const WGPEER_A_ALLOWEDIPS: u16 = 9;
+const WGALLOWEDIP_A_FAMILY: u16 = 1;
+const WGALLOWEDIP_A_IPADDR: u16 = 2;
+const WGALLOWEDIP_A_CIDR_MASK: u16 = 3;
+
+static CAN_READ: AtomicBool = AtomicBool::new(true);
+
+#[derive(Debug, Default)]
+pub struct WireguardPeer {
+    interface: String,
+    public_key: String,
+    endpoint: String,
+    allowed_ips: Vec<String>,
+    /// Unix timestamp of the last handshake, 0 if no handshake has happened yet
+    last_handshake: i64,
+    bytes_received: u64,
+    bytes_sent: u64,
+}
+
+impl SensorData for WireguardPeer {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"wireguard_peer_info{{host="{}", network="{}", peer="{}", endpoint="{}", allowed_ips="{}"}} 1"#,
+            hostname,
+            self.interface,
+            self.public_key,
+            self.endpoint,
+            self.allowed_ips.join(",")
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"wireguard_peer_last_handshake{{host="{}", network="{}", peer="{}"}} {}"#,
+            hostname, self.interface, self.public_key, self.last_handshake
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"wireguard_peer_received{{host="{}", network="{}", peer="{}"}} {}"#,
+            hostname, self.interface, self.public_key, self.bytes_received
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"wireguard_peer_sent{{host="{}", network="{}", peer="{}"}} {}"#,
+            hostname, self.interface, self.public_key, self.bytes_sent
+        )
+        .ok();
+    }
+}
+
+fn wireguard_interfaces() -> Vec<String> {
+    read_dir("/sys/class/net")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            read_to_string(entry.path().join("uevent"))
+                .unwrap_or_default()
+                .lines()
+                .any(|line| line == "DEVTYPE=wireguard")
+        })
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+/// Peer statistics for all wireguard interfaces
+///
+/// Querying wireguard devices requires `CAP_NET_ADMIN`, if the permission is missing
+/// a warning is logged once and the collector disables itself.
+pub fn peers() -> Vec<WireguardPeer> {
+    if !CAN_READ.load(Ordering::Relaxed) {
+        return Vec::new();
+    }
+    let interfaces = wireguard_interfaces();
+    if interfaces.is_empty() {
+        return Vec::new();
+    }
+
+    let mut netlink = match GenericNetlink::open("wireguard", 1) {
+        Ok(netlink) => netlink,
+        Err(e) => {
+            warn!(
+                error = display(e),
+                "Failed to open wireguard netlink socket"
+            );
+            return Vec::new();
+        }
+    };
+
+    let mut peers: Vec<WireguardPeer> = Vec::new();
+    for interface in interfaces {
+        let mut attributes = Vec::new();
+        let mut name = interface.as_bytes().to_vec();
+        name.push(0);
+        push_attribute(&mut attributes, WGDEVICE_A_IFNAME, &name);
+
+        let responses =
+            match netlink.request(WG_CMD_GET_DEVICE, libc::NLM_F_DUMP as u16, &attributes) {
+                Ok(responses) => responses,
+                Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+                    CAN_READ.store(false, Ordering::Relaxed);
+                    warn!(
+                        error = display(e),
+                        "Failed to query wireguard devices, CAP_NET_ADMIN is required"
+                    );
+                    return Vec::new();
+                }
+                Err(e) => {
+                    warn!(
+                        error = display(e),
+                        interface, "Failed to query wireguard device"
+                    );
+                    continue;
+                }
+            };
+
+        peers.extend(parse_device(&interface, &responses));
+    }
+    peers
+}
+
+/// Collect the peers of a device from the responses of a `WG_CMD_GET_DEVICE` dump
+fn parse_device(interface: &str, responses: &[Vec<u8>]) -> Vec<WireguardPeer> {
+    let mut peers: Vec<WireguardPeer> = Vec::new();
+    for response in responses {
+        let device_peers = Attributes::new(response)
+            .filter(|attribute| attribute.kind == WGDEVICE_A_PEERS)
+            .flat_map(|attribute| attribute.nested());
+        for peer in device_peers {
+            let peer = parse_peer(interface, peer);
+            // peers with many allowed ips can be split over multiple messages
+            match peers
+                .iter_mut()
+                .find(|existing| existing.public_key == peer.public_key)
+            {
+                Some(existing) => existing.allowed_ips.extend(peer.allowed_ips),
+                None => peers.push(peer),
+            }
+        }
+    }
+    peers
+}
+
+fn parse_peer(interface: &str, peer: Attribute) -> WireguardPeer {
+    let mut result = WireguardPeer {
+        interface: interface.into(),
+        ..WireguardPeer::default()
+    };
+    for attribute in peer.nested() {
+        match attribute.kind {
+            WGPEER_A_PUBLIC_KEY => result.public_key = BASE64_STANDARD.encode(attribute.data),
+            WGPEER_A_ENDPOINT => {
+                result.endpoint = parse_endpoint(attribute.data)
+                    .map(|endpoint| endpoint.to_string())
+                    .unwrap_or_default()
+            }
+            WGPEER_A_LAST_HANDSHAKE_TIME => {
+                // struct __kernel_timespec, we only need the seconds
+                result.last_handshake = attribute.u64().unwrap_or_default() as i64
+            }
+            WGPEER_A_RX_BYTES => result.bytes_received = attribute.u64().unwrap_or_default(),
+            WGPEER_A_TX_BYTES => result.bytes_sent = attribute.u64().unwrap_or_default(),
+            WGPEER_A_ALLOWEDIPS => result
+                .allowed_ips
+                .extend(attribute.nested().filter_map(parse_allowed_ip)),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// The endpoint is stored as a `sockaddr_in` or `sockaddr_in6`
+fn parse_endpoint(data: &[u8]) -> Option<SocketAddr> {
+    let family = u16::from_ne_bytes(data.get(0..2)?.try_into().ok()?);
+    let port = u16::from_be_bytes(data.get(2..4)?.try_into().ok()?);
+    match family as libc::c_int {
+        libc::AF_INET => {
+            let ip: [u8; 4] = data.get(4..8)?.try_into().ok()?;
+            Some(SocketAddr::new(Ipv4Addr::from(ip).into(), port))
+        }
+        libc::AF_INET6 => {
+            let ip: [u8; 16] = data.get(8..24)?.try_into().ok()?;
+            Some(SocketAddr::new(Ipv6Addr::from(ip).into(), port))
+        }
+        _ => None,
+    }
+}
+
+fn parse_allowed_ip(allowed_ip: Attribute) -> Option<String> {
+    let mut family = None;
+    let mut address = None;
+    let mut mask = None;
+    for attribute in allowed_ip.nested() {
+        match attribute.kind {
+            WGALLOWEDIP_A_FAMILY => family = attribute.u16(),
+            WGALLOWEDIP_A_IPADDR => address = Some(attribute.data),
+            WGALLOWEDIP_A_CIDR_MASK => mask = attribute.u8(),
+            _ => {}
+        }
+    }
+    let address = address?;
+    let ip = match family? as libc::c_int {
+        libc::AF_INET => Ipv4Addr::from(<[u8; 4]>::try_from(address).ok()?).to_string(),
+        libc::AF_INET6 => Ipv6Addr::from(<[u8; 16]>::try_from(address).ok()?).to_string(),
+        _ => return None,
+    };
+    Some(format!("{}/{}", ip, mask?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linux::net::netns::in_netns;
+
+    const KEY_A: [u8; 32] = [1; 32];
+    const KEY_B: [u8; 32] = [2; 32];
+
+    fn nested(attributes: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (kind, value) in attributes {
+            push_attribute(&mut data, *kind, value);
+        }
+        data
+    }
+
+    fn sockaddr_in(ip: [u8; 4], port: u16) -> Vec<u8> {
+        let mut data = (libc::AF_INET as u16).to_ne_bytes().to_vec();
+        data.extend_from_slice(&port.to_be_bytes());
+        data.extend_from_slice(&ip);
+        data.extend_from_slice(&[0; 8]);
+        data
+    }
+
+    fn sockaddr_in6(ip: Ipv6Addr, port: u16) -> Vec<u8> {
+        let mut data = (libc::AF_INET6 as u16).to_ne_bytes().to_vec();
+        data.extend_from_slice(&port.to_be_bytes());
+        // flow info
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&ip.octets());
+        // scope id
+        data.extend_from_slice(&[0; 4]);
+        data
+    }
+
+    fn allowed_ip(family: libc::c_int, address: &[u8], mask: u8) -> Vec<u8> {
+        nested(&[
+            (WGALLOWEDIP_A_FAMILY, (family as u16).to_ne_bytes().to_vec()),
+            (WGALLOWEDIP_A_IPADDR, address.to_vec()),
+            (WGALLOWEDIP_A_CIDR_MASK, vec![mask]),
+        ])
+    }
+
+    fn peer(key: [u8; 32], extra: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut attributes = vec![(WGPEER_A_PUBLIC_KEY, key.to_vec())];
+        attributes.extend_from_slice(extra);
+        nested(&attributes)
+    }
+
+    /// A device message with the given peers, each peer is nested in the peer list by index
+    fn device(peers: &[Vec<u8>]) -> Vec<u8> {
+        let mut message = Vec::new();
+        push_attribute(&mut message, WGDEVICE_A_IFNAME, b"wg0\0");
+        let list = peers
+            .iter()
+            .enumerate()
+            .map(|(index, peer)| (index as u16, peer.clone()))
+            .collect::<Vec<_>>();
+        push_attribute(&mut message, WGDEVICE_A_PEERS, &nested(&list));
+        message
+    }
+
+    #[test]
+    fn test_parse_endpoint_v4() {
+        assert_eq!(
+            parse_endpoint(&sockaddr_in([192, 0, 2, 1], 51820)),
+            Some("192.0.2.1:51820".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_endpoint_v6() {
+        assert_eq!(
+            parse_endpoint(&sockaddr_in6("2001:db8::1".parse().unwrap(), 51821)),
+            Some("[2001:db8::1]:51821".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_endpoint_invalid() {
+        assert_eq!(parse_endpoint(&[]), None);
+        assert_eq!(parse_endpoint(&sockaddr_in([192, 0, 2, 1], 1)[..6]), None);
+        let mut unknown = sockaddr_in([192, 0, 2, 1], 1);
+        unknown[0..2].copy_from_slice(&(libc::AF_UNIX as u16).to_ne_bytes());
+        assert_eq!(parse_endpoint(&unknown), None);
+    }
+
+    #[test]
+    fn test_parse_allowed_ip() {
+        let data = allowed_ip(libc::AF_INET, &[10, 0, 0, 0], 24);
+        assert_eq!(
+            parse_allowed_ip(Attribute {
+                kind: 0,
+                data: &data
+            }),
+            Some("10.0.0.0/24".into())
+        );
+        let address: Ipv6Addr = "fd00::2".parse().unwrap();
+        let data = allowed_ip(libc::AF_INET6, &address.octets(), 128);
+        assert_eq!(
+            parse_allowed_ip(Attribute {
+                kind: 0,
+                data: &data
+            }),
+            Some("fd00::2/128".into())
+        );
+        // address length doesn't match the family
+        let data = allowed_ip(libc::AF_INET6, &[10, 0, 0, 0], 24);
+        assert_eq!(
+            parse_allowed_ip(Attribute {
+                kind: 0,
+                data: &data
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_peer() {
+        let mut handshake = 1_700_000_000u64.to_ne_bytes().to_vec();
+        handshake.extend_from_slice(&123u64.to_ne_bytes());
+        let data = peer(
+            KEY_A,
+            &[
+                (WGPEER_A_ENDPOINT, sockaddr_in([192, 0, 2, 1], 51820)),
+                (WGPEER_A_LAST_HANDSHAKE_TIME, handshake),
+                (WGPEER_A_RX_BYTES, 1024u64.to_ne_bytes().to_vec()),
+                (WGPEER_A_TX_BYTES, 2048u64.to_ne_bytes().to_vec()),
+                (
+                    WGPEER_A_ALLOWEDIPS,
+                    nested(&[
+                        (0, allowed_ip(libc::AF_INET, &[10, 0, 0, 2], 32)),
+                        (1, allowed_ip(libc::AF_INET, &[10, 1, 0, 0], 16)),
+                    ]),
+                ),
+            ],
+        );
+        let peer = parse_peer(
+            "wg0",
+            Attribute {
+                kind: 0,
+                data: &data,
+            },
+        );
+        assert_eq!(peer.interface, "wg0");
+        assert_eq!(peer.public_key, BASE64_STANDARD.encode(KEY_A));
+        assert_eq!(peer.endpoint, "192.0.2.1:51820");
+        assert_eq!(peer.last_handshake, 1_700_000_000);
+        assert_eq!(peer.bytes_received, 1024);
+        assert_eq!(peer.bytes_sent, 2048);
+        assert_eq!(peer.allowed_ips, vec!["10.0.0.2/32", "10.1.0.0/16"]);
+    }
+
+    #[test]
+    fn test_parse_peer_without_handshake() {
+        let data = peer(KEY_B, &[]);
+        let peer = parse_peer(
+            "wg0",
+            Attribute {
+                kind: 0,
+                data: &data,
+            },
+        );
+        assert_eq!(peer.endpoint, "");
+        assert_eq!(peer.last_handshake, 0);
+        assert!(peer.allowed_ips.is_empty());
+    }
+
+    #[test]
+    fn test_parse_device_split_messages() {
+        let allowed_ips = |ips: &[[u8; 4]]| {
+            let list = ips
+                .iter()
+                .enumerate()
+                .map(|(index, ip)| (index as u16, allowed_ip(libc::AF_INET, ip, 32)))
+                .collect::<Vec<_>>();
+            (WGPEER_A_ALLOWEDIPS, nested(&list))
+        };
+        // the allowed ips of peer A continue in the second message
+        let responses = vec![
+            device(&[peer(KEY_A, &[allowed_ips(&[[10, 0, 0, 1], [10, 0, 0, 2]])])]),
+            device(&[
+                peer(KEY_A, &[allowed_ips(&[[10, 0, 0, 3]])]),
+                peer(KEY_B, &[allowed_ips(&[[10, 0, 1, 1]])]),
+            ]),
+        ];
+        let peers = parse_device("wg0", &responses);
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers[0].public_key, BASE64_STANDARD.encode(KEY_A));
+        assert_eq!(
+            peers[0].allowed_ips,
+            vec!["10.0.0.1/32", "10.0.0.2/32", "10.0.0.3/32"]
+        );
+        assert_eq!(peers[1].public_key, BASE64_STANDARD.encode(KEY_B));
+        assert_eq!(peers[1].allowed_ips, vec!["10.0.1.1/32"]);
+    }
+
+    #[test]
+    fn test_write() {
+        let peer = WireguardPeer {
+            interface: "wg0".into(),
+            public_key: "key".into(),
+            bytes_sent: 10,
+            ..WireguardPeer::default()
+        };
+        let mut out = String::new();
+        peer.write(&mut out, "host");
+        assert!(out.contains(r#"wireguard_peer_sent{host="host", network="wg0", peer="key"} 10"#));
+    }
+
+    /// Requires root, the wireguard kernel module and `wg` from wireguard-tools
+    #[test]
+    #[ignore = "requires root and wireguard"]
+    fn test_netns_peers() {
+        const PEER: &str = "eO3+A1ouzZzAVhQVLJP8cPeT2c58h7Wqzhs5nKB3dHc=";
+        let setup = [
+            "ip link add wg0 type wireguard".to_string(),
+            "wg genkey > /tmp/palantir-test-wg.key".to_string(),
+            format!("wg set wg0 private-key /tmp/palantir-test-wg.key listen-port 51820 peer {PEER} allowed-ips 10.0.0.2/32,fd00::2/128 endpoint 192.0.2.1:51820"),
+            "rm /tmp/palantir-test-wg.key".to_string(),
+            "ip link set wg0 up".to_string(),
+        ];
+        let setup = setup.iter().map(String::as_str).collect::<Vec<_>>();
+        if !in_netns("linux::net::wireguard::tests::test_netns_peers", &setup) {
+            return;
+        }
+
+        let peers = peers();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].interface, "wg0");
+        assert_eq!(peers[0].public_key, PEER);
+        assert_eq!(peers[0].endpoint, "192.0.2.1:51820");
+        assert_eq!(peers[0].allowed_ips, vec!["10.0.0.2/32", "fd00::2/128"]);
+        assert_eq!(peers[0].last_handshake, 0);
+    }
+}
@@ -0,0 +1,304 @@
+use crate::linux::net::netlink::{push_attribute, Attribute, Attributes, GenericNetlink};
+use crate::{escape_label, SensorData};
+use std::fmt::Write;
+use std::fs::{read_dir, read_to_string};
+use tracing::debug;
+
+// from linux/nl80211.h
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_CMD_GET_STATION: u8 = 17;
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_MAC: u16 = 6;
+const NL80211_ATTR_STA_INFO: u16 = 21;
+const NL80211_ATTR_SSID: u16 = 52;
+const NL80211_STA_INFO_RX_BYTES: u16 = 2;
+const NL80211_STA_INFO_TX_BYTES: u16 = 3;
+const NL80211_STA_INFO_SIGNAL: u16 = 7;
+const NL80211_STA_INFO_TX_BITRATE: u16 = 8;
+const NL80211_STA_INFO_TX_RETRIES: u16 = 11;
+const NL80211_STA_INFO_TX_FAILED: u16 = 12;
+const NL80211_STA_INFO_RX_BITRATE: u16 = 14;
+const NL80211_STA_INFO_RX_BYTES64: u16 = 23;
+const NL80211_STA_INFO_TX_BYTES64: u16 = 24;
+const NL80211_RATE_INFO_BITRATE: u16 = 1;
+const NL80211_RATE_INFO_BITRATE32: u16 = 5;
+
+#[derive(Debug, Default)]
+pub struct WirelessQuality {
+    interface: String,
+    link: f32,
+    level: f32,
+    noise: f32,
+    discarded_nwid: u64,
+    discarded_crypt: u64,
+    discarded_frag: u64,
+    discarded_retry: u64,
+    discarded_misc: u64,
+    missed_beacons: u64,
+}
+
+impl SensorData for WirelessQuality {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"wifi_link_quality{{host="{}", network="{}"}} {:.1}"#,
+            hostname, self.interface, self.link
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"wifi_signal_level{{host="{}", network="{}"}} {:.1}"#,
+            hostname, self.interface, self.level
+        )
+        .ok();
+        // -256 is used when the driver doesn't report noise
+        if self.noise > -256.0 {
+            writeln!(
+                &mut w,
+                r#"wifi_noise_level{{host="{}", network="{}"}} {:.1}"#,
+                hostname, self.interface, self.noise
+            )
+            .ok();
+        }
+        let discarded = [
+            ("nwid", self.discarded_nwid),
+            ("crypt", self.discarded_crypt),
+            ("frag", self.discarded_frag),
+            ("retry", self.discarded_retry),
+            ("misc", self.discarded_misc),
+        ];
+        for (reason, count) in discarded {
+            writeln!(
+                &mut w,
+                r#"wifi_discarded_packets{{host="{}", network="{}", reason="{}"}} {}"#,
+                hostname, self.interface, reason, count
+            )
+            .ok();
+        }
+        writeln!(
+            &mut w,
+            r#"wifi_missed_beacons{{host="{}", network="{}"}} {}"#,
+            hostname, self.interface, self.missed_beacons
+        )
+        .ok();
+    }
+}
+
+/// Link quality from the wireless extensions in `/proc/net/wireless`
+pub fn wireless_quality() -> Vec<WirelessQuality> {
+    let content = read_to_string("/proc/net/wireless").unwrap_or_default();
+    content
+        .lines()
+        .skip(2)
+        .filter_map(parse_wireless_line)
+        .collect()
+}
+
+/// Parse a line in the format `wlp3s0: 0000   54.  -56.  -256        0      0      0      0     10        0`
+fn parse_wireless_line(line: &str) -> Option<WirelessQuality> {
+    let (interface, values) = line.trim_start().split_once(':')?;
+    let mut parts = values.split_ascii_whitespace().skip(1);
+    let mut next_float = || parts.next()?.trim_end_matches('.').parse::<f32>().ok();
+    let link = next_float()?;
+    let level = next_float()?;
+    let noise = next_float()?;
+    let mut next_count = || parts.next()?.parse::<u64>().ok();
+    Some(WirelessQuality {
+        interface: interface.into(),
+        link,
+        level,
+        noise,
+        discarded_nwid: next_count()?,
+        discarded_crypt: next_count()?,
+        discarded_frag: next_count()?,
+        discarded_retry: next_count()?,
+        discarded_misc: next_count()?,
+        missed_beacons: next_count()?,
+    })
+}
+
+#[derive(Debug, Default)]
+pub struct WirelessStation {
+    /// The mac address of the station, for a client interface this is the bssid of the access point
+    mac: String,
+    signal: Option<i8>,
+    /// Bitrates in 100kbit/s
+    tx_bitrate: Option<u32>,
+    rx_bitrate: Option<u32>,
+    tx_retries: Option<u32>,
+    tx_failed: Option<u32>,
+    rx_bytes: Option<u64>,
+    tx_bytes: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct WirelessLink {
+    interface: String,
+    ssid: String,
+    stations: Vec<WirelessStation>,
+}
+
+impl SensorData for WirelessLink {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        for station in &self.stations {
+            writeln!(
+                &mut w,
+                r#"wifi_info{{host="{}", network="{}", ssid="{}", bssid="{}"}} 1"#,
+                hostname,
+                self.interface,
+                escape_label(&self.ssid),
+                station.mac
+            )
+            .ok();
+            if let Some(signal) = station.signal {
+                writeln!(
+                    &mut w,
+                    r#"wifi_station_signal{{host="{}", network="{}", bssid="{}"}} {}"#,
+                    hostname, self.interface, station.mac, signal
+                )
+                .ok();
+            }
+            let values = [
+                // in bits per second
+                (
+                    "tx_bitrate",
+                    station.tx_bitrate.map(|rate| rate as u64 * 100_000),
+                ),
+                (
+                    "rx_bitrate",
+                    station.rx_bitrate.map(|rate| rate as u64 * 100_000),
+                ),
+                ("tx_retries", station.tx_retries.map(u64::from)),
+                ("tx_failed", station.tx_failed.map(u64::from)),
+                ("received", station.rx_bytes),
+                ("sent", station.tx_bytes),
+            ];
+            for (name, value) in values {
+                if let Some(value) = value {
+                    writeln!(
+                        &mut w,
+                        r#"wifi_station_{}{{host="{}", network="{}", bssid="{}"}} {}"#,
+                        name, hostname, self.interface, station.mac, value
+                    )
+                    .ok();
+                }
+            }
+        }
+    }
+}
+
+fn wireless_interfaces() -> Vec<(String, u32)> {
+    read_dir("/sys/class/net")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join("phy80211").exists())
+        .filter_map(|entry| {
+            let index = read_to_string(entry.path().join("ifindex")).ok()?;
+            Some((
+                entry.file_name().into_string().ok()?,
+                index.trim().parse().ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// Connection details for all wireless interfaces from nl80211
+pub fn wireless_links() -> Vec<WirelessLink> {
+    let interfaces = wireless_interfaces();
+    if interfaces.is_empty() {
+        return Vec::new();
+    }
+    let mut netlink = match GenericNetlink::open("nl80211", 0) {
+        Ok(netlink) => netlink,
+        Err(e) => {
+            debug!(error = display(e), "Failed to open nl80211 netlink socket");
+            return Vec::new();
+        }
+    };
+
+    interfaces
+        .into_iter()
+        .map(|(interface, index)| {
+            let mut attributes = Vec::new();
+            push_attribute(&mut attributes, NL80211_ATTR_IFINDEX, &index.to_ne_bytes());
+
+            let ssid = netlink
+                .request(NL80211_CMD_GET_INTERFACE, 0, &attributes)
+                .unwrap_or_default()
+                .iter()
+                .flat_map(|response| Attributes::new(response))
+                .find(|attribute| attribute.kind == NL80211_ATTR_SSID)
+                .map(|attribute| String::from_utf8_lossy(attribute.data).into_owned())
+                .unwrap_or_default();
+            let stations = netlink
+                .request(
+                    NL80211_CMD_GET_STATION,
+                    libc::NLM_F_DUMP as u16,
+                    &attributes,
+                )
+                .unwrap_or_default()
+                .iter()
+                .map(|response| parse_station(Attributes::new(response)))
+                .collect();
+
+            WirelessLink {
+                interface,
+                ssid,
+                stations,
+            }
+        })
+        .collect()
+}
+
+fn parse_station(attributes: Attributes) -> WirelessStation {
+    let mut station = WirelessStation::default();
+    for attribute in attributes {
+        match attribute.kind {
+            NL80211_ATTR_MAC => {
+                station.mac = attribute
+                    .data
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<Vec<_>>()
+                    .join(":")
+            }
+            NL80211_ATTR_STA_INFO => {
+                for info in attribute.nested() {
+                    match info.kind {
+                        NL80211_STA_INFO_SIGNAL => station.signal = info.u8().map(|s| s as i8),
+                        NL80211_STA_INFO_TX_BITRATE => station.tx_bitrate = parse_bitrate(info),
+                        NL80211_STA_INFO_RX_BITRATE => station.rx_bitrate = parse_bitrate(info),
+                        NL80211_STA_INFO_TX_RETRIES => station.tx_retries = info.u32(),
+                        NL80211_STA_INFO_TX_FAILED => station.tx_failed = info.u32(),
+                        // prefer the 64bit counters when available
+                        NL80211_STA_INFO_RX_BYTES if station.rx_bytes.is_none() => {
+                            station.rx_bytes = info.u32().map(u64::from)
+                        }
+                        NL80211_STA_INFO_TX_BYTES if station.tx_bytes.is_none() => {
+                            station.tx_bytes = info.u32().map(u64::from)
+                        }
+                        NL80211_STA_INFO_RX_BYTES64 => station.rx_bytes = info.u64(),
+                        NL80211_STA_INFO_TX_BYTES64 => station.tx_bytes = info.u64(),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    station
+}
+
+/// The bitrate is reported as a 32bit value, with a 16bit fallback for older kernels
+fn parse_bitrate(rate_info: Attribute) -> Option<u32> {
+    let mut bitrate = None;
+    for attribute in rate_info.nested() {
+        match attribute.kind {
+            NL80211_RATE_INFO_BITRATE32 => return attribute.u32(),
+            NL80211_RATE_INFO_BITRATE => bitrate = attribute.u16().map(u32::from),
+            _ => {}
+        }
+    }
+    bitrate
+}
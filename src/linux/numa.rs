@@ -0,0 +1,82 @@
+use crate::linux::sensors::meminfo_metric_name;
+use crate::SensorData;
+use std::fmt::Write;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct NumaNode {
+    node: String,
+    memory: Vec<(String, u64)>,
+    stats: Vec<(String, u64)>,
+}
+
+impl SensorData for NumaNode {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        for (name, value) in &self.memory {
+            writeln!(
+                &mut w,
+                r#"numa_memory_{}{{host="{}", node="{}"}} {}"#,
+                name, hostname, self.node, value
+            )
+            .ok();
+        }
+        for (name, value) in &self.stats {
+            writeln!(
+                &mut w,
+                r#"numa_{}{{host="{}", node="{}"}} {}"#,
+                name, hostname, self.node, value
+            )
+            .ok();
+        }
+    }
+}
+
+pub fn nodes() -> Vec<NumaNode> {
+    read_dir("/sys/devices/system/node")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let node = file_name.to_str()?.strip_prefix("node")?;
+            if !node.bytes().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            Some(numa_node(node.into(), &entry.path()))
+        })
+        .collect()
+}
+
+fn numa_node(node: String, path: &Path) -> NumaNode {
+    let meminfo = read_to_string(path.join("meminfo")).unwrap_or_default();
+    let numastat = read_to_string(path.join("numastat")).unwrap_or_default();
+
+    NumaNode {
+        node,
+        memory: meminfo.lines().filter_map(parse_meminfo_line).collect(),
+        stats: numastat
+            .lines()
+            .filter_map(|line| {
+                let (name, value) = line.split_once(' ')?;
+                // numa_hit/numa_miss/numa_foreign already carry the prefix, local_node/other_node don't
+                let name = name.strip_prefix("numa_").unwrap_or(name);
+                Some((name.into(), value.trim().parse().ok()?))
+            })
+            .collect(),
+    }
+}
+
+/// Parse a line in the format `Node 0 MemTotal:        6147400 kB`
+fn parse_meminfo_line(line: &str) -> Option<(String, u64)> {
+    let (name, value) = line.split_once(':')?;
+    let name = name.rsplit(' ').next()?;
+    // strip the prefix from MemTotal/MemFree/MemUsed to match the system-wide memory metrics
+    let name = name.strip_prefix("Mem").unwrap_or(name);
+    let value = value.trim();
+    let value = match value.strip_suffix(" kB") {
+        Some(value) => value.parse::<u64>().ok()? * 1024,
+        None => value.parse().ok()?,
+    };
+    Some((meminfo_metric_name(name), value))
+}
@@ -0,0 +1,450 @@
+use super::{ProbeKind, ProbeResult};
+use once_cell::sync::Lazy;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+static TLS_CONFIG: Lazy<Option<Arc<ClientConfig>>> = Lazy::new(|| {
+    let mut roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Ok(ca_file) = dotenvy::var("PROBE_CA_FILE") {
+        add_ca_file(&mut roots, &ca_file);
+    }
+    let config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| warn!(error = display(e), "Failed to setup tls"))
+            .ok()?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+    Some(Arc::new(config))
+});
+
+/// Trust the certificates from a pem bundle in addition to the webpki roots, for services using a private ca
+fn add_ca_file(roots: &mut RootCertStore, ca_file: &str) {
+    let certificates = match CertificateDer::pem_file_iter(ca_file) {
+        Ok(certificates) => certificates,
+        Err(e) => {
+            warn!(error = display(e), ca_file, "Failed to read probe ca file");
+            return;
+        }
+    };
+    for certificate in certificates {
+        if let Err(e) = certificate
+            .map_err(|e| e.to_string())
+            .and_then(|certificate| roots.add(certificate).map_err(|e| e.to_string()))
+        {
+            warn!(error = e, ca_file, "Invalid certificate in probe ca file");
+        }
+    }
+}
+
+struct Url<'a> {
+    tls: bool,
+    /// Host with optional port
+    authority: &'a str,
+    /// Host without the brackets around ipv6 literals
+    host: &'a str,
+    port: Option<u16>,
+    path: &'a str,
+}
+
+impl<'a> Url<'a> {
+    fn parse(url: &'a str) -> Option<Url<'a>> {
+        let (scheme, rest) = url.split_once("://")?;
+        let tls = match scheme {
+            "http" => false,
+            "https" => true,
+            _ => return None,
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.strip_prefix('[') {
+            // ipv6 literal
+            Some(ipv6) => {
+                let (host, rest) = ipv6.split_once(']')?;
+                match rest {
+                    "" => (host, None),
+                    rest => (host, Some(rest.strip_prefix(':')?)),
+                }
+            }
+            None => match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        let port = port.map(str::parse).transpose().ok()?;
+        Some(Url {
+            tls,
+            authority,
+            host,
+            port,
+            path,
+        })
+    }
+
+    fn default_port(&self) -> u16 {
+        if self.tls {
+            443
+        } else {
+            80
+        }
+    }
+
+    fn address(&self) -> Option<SocketAddr> {
+        let port = self.port.unwrap_or_else(|| self.default_port());
+        (self.host, port).to_socket_addrs().ok()?.next()
+    }
+}
+
+pub fn probe(target: &str, timeout: Duration) -> ProbeResult {
+    let mut result = ProbeResult::new(ProbeKind::Http, target);
+    let Some(url) = Url::parse(target) else {
+        warn!(target, "Invalid http probe url");
+        return result;
+    };
+    let Some(address) = url.address() else {
+        warn!(target, "Failed to resolve http probe target");
+        return result;
+    };
+
+    let start = Instant::now();
+    let Ok(stream) = TcpStream::connect_timeout(&address, timeout) else {
+        return result;
+    };
+    if stream.set_read_timeout(Some(timeout)).is_err()
+        || stream.set_write_timeout(Some(timeout)).is_err()
+    {
+        return result;
+    }
+
+    let status = if url.tls {
+        let Some(connection) = TLS_CONFIG.clone().and_then(|config| {
+            let name = ServerName::try_from(url.host.to_string()).ok()?;
+            ClientConnection::new(config, name).ok()
+        }) else {
+            return result;
+        };
+        let mut stream = StreamOwned::new(connection, stream);
+        let status = request(&mut stream, &url);
+        result.certificate_expiry = stream
+            .conn
+            .peer_certificates()
+            .and_then(|certificates| certificates.first())
+            .and_then(|certificate| certificate_expiry(certificate));
+        status
+    } else {
+        request(stream, &url)
+    };
+
+    if let Ok(status) = status {
+        result.latency = Some(start.elapsed());
+        result.status = Some(status);
+        result.success = (200..400).contains(&status);
+    }
+    result
+}
+
+/// Send a GET request and read the status code from the response
+fn request<S: Read + Write>(mut stream: S, url: &Url) -> io::Result<u16> {
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: palantir\r\nConnection: close\r\n\r\n",
+        url.path, url.authority
+    )?;
+    stream.flush()?;
+
+    let mut response = Vec::new();
+    let mut buff = [0; 256];
+    while !response.windows(2).any(|window| window == b"\r\n") {
+        let read = stream.read(&mut buff)?;
+        if read == 0 || response.len() > 4096 {
+            return Err(ErrorKind::InvalidData.into());
+        }
+        response.extend_from_slice(&buff[..read]);
+    }
+
+    // HTTP/1.1 200 OK, the reason phrase is optional
+    std::str::from_utf8(&response)
+        .ok()
+        .and_then(|response| response.split("\r\n").next())
+        .and_then(|status_line| status_line.split(' ').nth(1))
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| ErrorKind::InvalidData.into())
+}
+
+/// Read a single DER encoded value, returning the tag, the content and the remaining data
+fn read_der(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *data.first()?;
+    let first = *data.get(1)? as usize;
+    let (len, offset) = if first < 0x80 {
+        (first, 2)
+    } else {
+        let count = first & 0x7f;
+        // lengths that don't fit in a usize can't be valid
+        if count > size_of::<usize>() {
+            return None;
+        }
+        let len = data
+            .get(2..2 + count)?
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+        (len, 2 + count)
+    };
+    let end = offset.checked_add(len)?;
+    let content = data.get(offset..end)?;
+    Some((tag, content, &data[end..]))
+}
+
+/// Get the "not after" time from a DER encoded X.509 certificate as unix timestamp
+///
+/// ```text
+/// Certificate ::= SEQUENCE {
+///     tbsCertificate SEQUENCE {
+///         version [0] EXPLICIT Version OPTIONAL,
+///         serialNumber INTEGER,
+///         signature AlgorithmIdentifier,
+///         issuer Name,
+///         validity SEQUENCE { notBefore Time, notAfter Time },
+///         ...
+/// ```
+fn certificate_expiry(certificate: &[u8]) -> Option<i64> {
+    let (_, certificate, _) = read_der(certificate)?;
+    let (_, tbs, _) = read_der(certificate)?;
+    let (tag, _, mut rest) = read_der(tbs)?;
+    if tag == 0xa0 {
+        // skip the version
+        rest = read_der(rest)?.2;
+    }
+    let (_, _, rest) = read_der(rest)?;
+    let (_, _, rest) = read_der(rest)?;
+    let (_, validity, _) = read_der(rest)?;
+    let (_, _, validity) = read_der(validity)?;
+    let (tag, not_after, _) = read_der(validity)?;
+    parse_time(tag, std::str::from_utf8(not_after).ok()?)
+}
+
+/// Parse an ASN.1 UTCTime (`YYMMDDHHMMSSZ`) or GeneralizedTime (`YYYYMMDDHHMMSSZ`)
+fn parse_time(tag: u8, time: &str) -> Option<i64> {
+    let time = time.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        0x17 => {
+            let year: i64 = time.get(0..2)?.parse().ok()?;
+            (
+                if year < 50 { 2000 + year } else { 1900 + year },
+                &time[2..],
+            )
+        }
+        0x18 => (time.get(0..4)?.parse().ok()?, &time[4..]),
+        _ => return None,
+    };
+    let field = |index: usize| -> Option<i64> { rest.get(index * 2..index * 2 + 2)?.parse().ok() };
+    let (month, day) = (field(0)?, field(1)?);
+    let (hour, minute, second) = (field(2)?, field(3)?, field(4)?);
+
+    // days since the epoch from the civil date
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    Some(days * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread::spawn;
+
+    fn certificate(pem: &str) -> CertificateDer<'static> {
+        CertificateDer::from_pem_slice(pem.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_certificate_expiry_utc_time() {
+        let certificate = certificate(include_str!(
+            "../../../tests/fixtures/certificates/utctime.pem"
+        ));
+        // 2030-06-15 12:30:45 UTC
+        assert_eq!(certificate_expiry(&certificate), Some(1907757045));
+    }
+
+    #[test]
+    fn test_certificate_expiry_generalized_time() {
+        let certificate = certificate(include_str!(
+            "../../../tests/fixtures/certificates/generalizedtime.pem"
+        ));
+        // 2060-02-29 23:59:59 UTC
+        assert_eq!(certificate_expiry(&certificate), Some(2845324799));
+    }
+
+    #[test]
+    fn test_certificate_expiry_without_version() {
+        let certificate = certificate(include_str!("../../../tests/fixtures/certificates/v1.pem"));
+        // 2035-01-01 00:00:00 UTC
+        assert_eq!(certificate_expiry(&certificate), Some(2051222400));
+    }
+
+    #[test]
+    fn test_certificate_expiry_invalid() {
+        assert_eq!(certificate_expiry(&[]), None);
+        assert_eq!(certificate_expiry(&[0x30, 0x82, 0xff]), None);
+    }
+
+    #[test]
+    fn test_add_ca_file() {
+        let mut roots = RootCertStore::empty();
+        add_ca_file(
+            &mut roots,
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/certificates/utctime.pem"
+            ),
+        );
+        assert_eq!(roots.len(), 1);
+
+        let mut roots = RootCertStore::empty();
+        add_ca_file(&mut roots, "/nonexistent/ca.pem");
+        assert!(roots.is_empty());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time(0x17, "700101000000Z"), Some(0));
+        assert_eq!(parse_time(0x17, "491231235959Z"), Some(2524607999));
+        assert_eq!(parse_time(0x18, "20000229120000Z"), Some(951825600));
+        assert_eq!(parse_time(0x17, "700101000000"), None);
+        assert_eq!(parse_time(0x02, "700101000000Z"), None);
+    }
+
+    /// Serve a single http response on a loopback port
+    fn serve(response: &'static str) -> u16 {
+        serve_on("127.0.0.1:0", response)
+    }
+
+    fn serve_on(address: &str, response: &'static str) -> u16 {
+        let listener = TcpListener::bind(address).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // read the full request, closing the socket with unread data resets the connection
+            let mut request = Vec::new();
+            let mut buff = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buff).unwrap();
+                assert_ne!(read, 0);
+                request.extend_from_slice(&buff[..read]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        port
+    }
+
+    #[test]
+    fn test_probe_http() {
+        let port = serve("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+        let result = probe(
+            &format!("http://127.0.0.1:{port}/health"),
+            Duration::from_secs(5),
+        );
+        assert!(result.success);
+        assert_eq!(result.status, Some(200));
+        assert!(result.latency.is_some());
+        assert_eq!(result.certificate_expiry, None);
+    }
+
+    #[test]
+    fn test_probe_http_without_reason_phrase() {
+        let port = serve("HTTP/1.1 204\r\nDate: Thu, 01 Jan 2026 00:00:00 GMT\r\n\r\n");
+        let result = probe(&format!("http://127.0.0.1:{port}"), Duration::from_secs(5));
+        assert!(result.success);
+        assert_eq!(result.status, Some(204));
+    }
+
+    #[test]
+    fn test_probe_http_error_status() {
+        let port = serve("HTTP/1.1 503 Service Unavailable\r\n\r\n");
+        let result = probe(&format!("http://127.0.0.1:{port}/"), Duration::from_secs(5));
+        assert!(!result.success);
+        assert_eq!(result.status, Some(503));
+    }
+
+    #[test]
+    fn test_probe_http_closed_port() {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let result = probe(&format!("http://127.0.0.1:{port}/"), Duration::from_secs(5));
+        assert!(!result.success);
+        assert_eq!(result.status, None);
+    }
+
+    #[test]
+    fn test_read_der_length_overflow() {
+        // length of length larger than a usize
+        assert_eq!(read_der(&[0x30, 0x89, 1, 0, 0, 0, 0, 0, 0, 0, 0]), None);
+        // length that overflows the offset
+        assert_eq!(
+            read_der(&[0x30, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            None
+        );
+        assert_eq!(
+            read_der(&[0x04, 0x81, 0x01, 0xaa, 0xbb]),
+            Some((0x04, &[0xaa][..], &[0xbb][..]))
+        );
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let url = Url::parse("https://example.com/health").unwrap();
+        assert_eq!(
+            (url.host, url.port, url.path),
+            ("example.com", None, "/health")
+        );
+        let url = Url::parse("http://example.com:8080").unwrap();
+        assert_eq!(
+            (url.host, url.port, url.path),
+            ("example.com", Some(8080), "/")
+        );
+        let url = Url::parse("http://[::1]/").unwrap();
+        assert_eq!((url.authority, url.host, url.port), ("[::1]", "::1", None));
+        let url = Url::parse("http://[::1]:8080/").unwrap();
+        assert_eq!((url.host, url.port), ("::1", Some(8080)));
+        assert!(Url::parse("http://[::1]8080/").is_none());
+        assert!(Url::parse("http://example.com:http/").is_none());
+        assert!(Url::parse("ftp://example.com/").is_none());
+    }
+
+    #[test]
+    fn test_ipv6_address() {
+        let url = Url::parse("http://[::1]/").unwrap();
+        assert_eq!(url.address(), Some("[::1]:80".parse().unwrap()));
+        let url = Url::parse("https://[::1]").unwrap();
+        assert_eq!(url.address(), Some("[::1]:443".parse().unwrap()));
+        let url = Url::parse("http://[::1]:8080/").unwrap();
+        assert_eq!(url.address(), Some("[::1]:8080".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_probe_http_ipv6() {
+        if TcpListener::bind("[::1]:0").is_err() {
+            // no ipv6 loopback available
+            return;
+        }
+        let port = serve_on("[::1]:0", "HTTP/1.1 200 OK\r\n\r\n");
+        let result = probe(&format!("http://[::1]:{port}/"), Duration::from_secs(5));
+        assert!(result.success);
+        assert_eq!(result.status, Some(200));
+    }
+}
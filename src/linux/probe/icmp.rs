@@ -0,0 +1,93 @@
+use super::{resolve, ProbeKind, ProbeResult};
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, UdpSocket};
+use std::os::fd::FromRawFd;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Number of echo requests sent for every probe
+const PING_COUNT: u16 = 3;
+const ICMP_ECHO_REQUEST: u8 = 8;
+const ICMP_ECHO_REPLY: u8 = 0;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+const ICMPV6_ECHO_REPLY: u8 = 129;
+
+/// Open an unprivileged icmp "ping" socket
+///
+/// The ping socket behaves like a udp socket, the kernel takes care of the identifier and checksum.
+fn ping_socket(address: &SocketAddr) -> io::Result<UdpSocket> {
+    let (domain, protocol) = match address {
+        SocketAddr::V4(_) => (libc::AF_INET, libc::IPPROTO_ICMP),
+        SocketAddr::V6(_) => (libc::AF_INET6, libc::IPPROTO_ICMPV6),
+    };
+    let fd = unsafe { libc::socket(domain, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, protocol) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { UdpSocket::from_raw_fd(fd) })
+}
+
+/// Returns `None` if ping sockets aren't allowed for our group (see `net.ipv4.ping_group_range`)
+pub fn probe(target: &str, timeout: Duration) -> Option<ProbeResult> {
+    let mut result = ProbeResult::new(ProbeKind::Icmp, target);
+    let Some(address) = resolve(target, 0) else {
+        warn!(target, "Failed to resolve icmp probe target");
+        return Some(result);
+    };
+    let socket = match ping_socket(&address) {
+        Ok(socket) => socket,
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+            warn!(
+                error = display(e),
+                target,
+                "Unprivileged icmp sockets are not allowed, check net.ipv4.ping_group_range"
+            );
+            return None;
+        }
+        Err(e) => {
+            warn!(error = display(e), target, "Failed to open icmp socket");
+            return Some(result);
+        }
+    };
+    if socket.connect(address).is_err() || socket.set_read_timeout(Some(timeout)).is_err() {
+        return Some(result);
+    }
+
+    let (request, reply) = match address {
+        SocketAddr::V4(_) => (ICMP_ECHO_REQUEST, ICMP_ECHO_REPLY),
+        SocketAddr::V6(_) => (ICMPV6_ECHO_REQUEST, ICMPV6_ECHO_REPLY),
+    };
+    let mut received = Vec::new();
+    for sequence in 0..PING_COUNT {
+        if let Some(rtt) = ping(&socket, request, reply, sequence) {
+            received.push(rtt);
+        }
+    }
+
+    result.loss = Some(1.0 - received.len() as f32 / PING_COUNT as f32);
+    if !received.is_empty() {
+        result.success = true;
+        result.latency = Some(received.iter().sum::<Duration>() / received.len() as u32);
+    }
+    Some(result)
+}
+
+/// Send a single echo request and wait for the matching reply
+fn ping(socket: &UdpSocket, request: u8, reply: u8, sequence: u16) -> Option<Duration> {
+    // type, code, checksum, identifier, sequence, the checksum and identifier are filled by the kernel
+    let mut packet = [
+        request, 0, 0, 0, 0, 0, 0, 0, b'p', b'a', b'l', b'a', b'n', b't', b'i', b'r',
+    ];
+    packet[6..8].copy_from_slice(&sequence.to_be_bytes());
+
+    let start = Instant::now();
+    socket.send(&packet).ok()?;
+    let mut buff = [0; 64];
+    loop {
+        // the read timeout ends the loop if no reply arrives
+        let len = socket.recv(&mut buff).ok()?;
+        if len >= 8 && buff[0] == reply && buff[6..8] == packet[6..8] {
+            return Some(start.elapsed());
+        }
+    }
+}
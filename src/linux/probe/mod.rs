@@ -0,0 +1,228 @@
+//! Synthetic reachability probes for configured targets
+//!
+//! Every target is probed from its own background thread, the latest result for each target
+//! is kept until the next scrape.
+
+use crate::{Result, SensorData};
+use std::fmt::Write;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::thread::{sleep, spawn};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+mod http;
+mod icmp;
+
+static PROBE_RESULTS: Mutex<Vec<ProbeResult>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProbeKind {
+    Icmp,
+    Tcp,
+    Http,
+}
+
+impl ProbeKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProbeKind::Icmp => "icmp",
+            ProbeKind::Tcp => "tcp",
+            ProbeKind::Http => "http",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    kind: ProbeKind,
+    target: String,
+    success: bool,
+    latency: Option<Duration>,
+    /// Ratio of lost echo requests
+    loss: Option<f32>,
+    status: Option<u16>,
+    /// Unix timestamp at which the tls certificate expires
+    certificate_expiry: Option<i64>,
+}
+
+impl ProbeResult {
+    fn new(kind: ProbeKind, target: &str) -> ProbeResult {
+        ProbeResult {
+            kind,
+            target: target.into(),
+            success: false,
+            latency: None,
+            loss: None,
+            status: None,
+            certificate_expiry: None,
+        }
+    }
+}
+
+impl SensorData for ProbeResult {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        let kind = self.kind.as_str();
+        writeln!(
+            &mut w,
+            r#"probe_success{{host="{}", type="{}", target="{}"}} {}"#,
+            hostname, kind, self.target, self.success as u8
+        )
+        .ok();
+        if let Some(latency) = self.latency {
+            writeln!(
+                &mut w,
+                r#"probe_latency{{host="{}", type="{}", target="{}"}} {:.6}"#,
+                hostname,
+                kind,
+                self.target,
+                latency.as_secs_f64()
+            )
+            .ok();
+        }
+        if let Some(loss) = self.loss {
+            writeln!(
+                &mut w,
+                r#"probe_icmp_loss{{host="{}", target="{}"}} {:.2}"#,
+                hostname, self.target, loss
+            )
+            .ok();
+        }
+        if let Some(status) = self.status {
+            writeln!(
+                &mut w,
+                r#"probe_http_status{{host="{}", target="{}"}} {}"#,
+                hostname, self.target, status
+            )
+            .ok();
+        }
+        if let Some(expiry) = self.certificate_expiry {
+            writeln!(
+                &mut w,
+                r#"probe_tls_expiry{{host="{}", target="{}"}} {}"#,
+                hostname, self.target, expiry
+            )
+            .ok();
+        }
+    }
+}
+
+/// The latest result of all configured probes
+pub fn probe_results() -> Vec<ProbeResult> {
+    PROBE_RESULTS.lock().unwrap().clone()
+}
+
+fn store_result(result: ProbeResult) {
+    let mut results = PROBE_RESULTS.lock().unwrap();
+    match results
+        .iter_mut()
+        .find(|existing| existing.kind == result.kind && existing.target == result.target)
+    {
+        Some(existing) => *existing = result,
+        None => results.push(result),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeConfig {
+    targets: Vec<(ProbeKind, String)>,
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl ProbeConfig {
+    /// Configured with the comma separated target lists `PROBE_ICMP` (hosts), `PROBE_TCP` (`host:port`)
+    /// and `PROBE_HTTP` (urls), the interval and timeout are configured in seconds with `PROBE_INTERVAL`
+    /// and `PROBE_TIMEOUT`
+    pub fn from_env() -> Result<ProbeConfig> {
+        let mut targets = Vec::new();
+        for (kind, var) in [
+            (ProbeKind::Icmp, "PROBE_ICMP"),
+            (ProbeKind::Tcp, "PROBE_TCP"),
+            (ProbeKind::Http, "PROBE_HTTP"),
+        ] {
+            let list = dotenvy::var(var).unwrap_or_default();
+            targets.extend(
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|target| !target.is_empty())
+                    .map(|target| (kind, target.to_string())),
+            );
+        }
+        let seconds = |var: &str, default: u64| -> Result<Duration> {
+            Ok(Duration::from_secs(match dotenvy::var(var) {
+                Ok(value) => value.parse()?,
+                Err(_) => default,
+            }))
+        };
+
+        Ok(ProbeConfig {
+            targets,
+            interval: seconds("PROBE_INTERVAL", 30)?,
+            timeout: seconds("PROBE_TIMEOUT", 5)?,
+        })
+    }
+}
+
+/// Start a background thread for every configured probe target
+pub fn start_probes(config: ProbeConfig) {
+    for (kind, target) in config.targets {
+        let (interval, timeout) = (config.interval, config.timeout);
+        spawn(move || loop {
+            let start = Instant::now();
+            let result = match kind {
+                ProbeKind::Icmp => icmp::probe(&target, timeout),
+                ProbeKind::Tcp => Some(probe_tcp(&target, timeout)),
+                ProbeKind::Http => Some(http::probe(&target, timeout)),
+            };
+            let Some(result) = result else {
+                // the probe can't run on this system, a warning has been logged already
+                return;
+            };
+            store_result(result);
+            sleep(interval.saturating_sub(start.elapsed()));
+        });
+    }
+}
+
+fn resolve(target: &str, default_port: u16) -> Option<SocketAddr> {
+    let addresses = match target.to_socket_addrs() {
+        Ok(addresses) => addresses,
+        Err(_) => (target, default_port).to_socket_addrs().ok()?,
+    };
+    addresses.into_iter().next()
+}
+
+fn probe_tcp(target: &str, timeout: Duration) -> ProbeResult {
+    let mut result = ProbeResult::new(ProbeKind::Tcp, target);
+    let Some(address) = resolve(target, 0) else {
+        warn!(target, "Failed to resolve tcp probe target");
+        return result;
+    };
+    let start = Instant::now();
+    if TcpStream::connect_timeout(&address, timeout).is_ok() {
+        result.success = true;
+        result.latency = Some(start.elapsed());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_probe_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let target = listener.local_addr().unwrap().to_string();
+        let result = probe_tcp(&target, Duration::from_secs(5));
+        assert!(result.success);
+        assert!(result.latency.is_some());
+
+        drop(listener);
+        let result = probe_tcp(&target, Duration::from_secs(5));
+        assert!(!result.success);
+        assert!(result.latency.is_none());
+    }
+}
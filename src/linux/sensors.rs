@@ -1,6 +1,6 @@
-use crate::data::{CpuTime, Memory, NetStats, Temperatures};
+use crate::data::{CpuTime, Memory, Temperatures};
 use crate::linux::hwmon::{Device, FileSource};
-use crate::{Error, IoResultExt, MultiSensorSource, Result, SensorSource};
+use crate::{Error, IoResultExt, Result, SensorSource};
 use std::fs::File;
 use std::io;
 use std::io::{BufRead, BufReader, ErrorKind, Read, Seek};
@@ -107,16 +107,20 @@ impl SensorSource for MemorySource {
 
         let mut mem = Memory::default();
         for line in self.buff.lines() {
-            if let Some(line) = line.strip_suffix(" kB") {
-                if let Some(line_total) = line.strip_prefix("MemTotal: ") {
-                    mem.total = line_total.trim().parse::<u64>()? * 1000;
-                }
-                if let Some(line_free) = line.strip_prefix("MemFree: ") {
-                    mem.free = line_free.trim().parse::<u64>()? * 1000;
-                }
-                if let Some(line_available) = line.strip_prefix("MemAvailable: ") {
-                    mem.available = line_available.trim().parse::<u64>()? * 1000;
-                }
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            // values are reported in KiB, except for the hugepage counts
+            let value = match value.strip_suffix(" kB") {
+                Some(value) => value.parse::<u64>()? * 1024,
+                None => value.parse::<u64>()?,
+            };
+            match name {
+                "MemTotal" => mem.total = value,
+                "MemFree" => mem.free = value,
+                "MemAvailable" => mem.available = value,
+                _ => mem.details.push((meminfo_metric_name(name), value)),
             }
         }
 
@@ -124,6 +128,37 @@ impl SensorSource for MemorySource {
     }
 }
 
+/// Convert a meminfo field name into a metric name suffix
+///
+/// `SwapTotal` becomes `swap_total`, `Active(anon)` becomes `active_anon` and `HugePages_Free` becomes `huge_pages_free`
+pub(crate) fn meminfo_metric_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    let mut previous_lower = false;
+    for c in name.chars() {
+        match c {
+            '(' | '_' => {
+                if !result.ends_with('_') {
+                    result.push('_');
+                }
+                previous_lower = false;
+            }
+            ')' => {}
+            c if c.is_ascii_uppercase() => {
+                if previous_lower {
+                    result.push('_');
+                }
+                result.push(c.to_ascii_lowercase());
+                previous_lower = false;
+            }
+            c => {
+                result.push(c);
+                previous_lower = c.is_ascii_lowercase();
+            }
+        }
+    }
+    result
+}
+
 pub struct CpuTimeSource {
     source: BufReader<File>,
     buff: Vec<u8>,
@@ -171,93 +206,3 @@ impl SensorSource for CpuTimeSource {
         }
     }
 }
-
-pub struct NetworkSource {
-    source: File,
-    buff: String,
-}
-
-impl NetworkSource {
-    pub fn new() -> Result<NetworkSource> {
-        Ok(NetworkSource {
-            source: File::open("/proc/net/dev").context("error opening netdev")?,
-            buff: String::new(),
-        })
-    }
-
-    fn parse_line(line: &str) -> Result<NetStats> {
-        let mut parts = line.trim_start().split_ascii_whitespace();
-        if let (
-            Some(interface),
-            Some(bytes_received),
-            _packets,
-            _err,
-            _drop,
-            _fifo,
-            _frame,
-            _compressed,
-            _multicast,
-            Some(bytes_sent),
-        ) = (
-            parts.next(),
-            parts.next(),
-            parts.next(),
-            parts.next(),
-            parts.next(),
-            parts.next(),
-            parts.next(),
-            parts.next(),
-            parts.next(),
-            parts.next(),
-        ) {
-            Ok(NetStats {
-                interface: interface.trim_end_matches(':').into(),
-                bytes_sent: bytes_sent.parse()?,
-                bytes_received: bytes_received.parse()?,
-            })
-        } else {
-            Err(Error::io(
-                "error reading netdev",
-                ErrorKind::InvalidData.into(),
-            ))
-        }
-    }
-}
-
-impl MultiSensorSource for NetworkSource {
-    type Data = NetStats;
-    type Iter<'a> = NetworkStatParser<'a>;
-
-    fn read(&mut self) -> Result<Self::Iter<'_>> {
-        self.buff.clear();
-        self.source.rewind().context("error rewinding netdev")?;
-        self.source
-            .read_to_string(&mut self.buff)
-            .context("error reading netdev")?;
-
-        Ok(NetworkStatParser {
-            lines: self.buff.lines(),
-        })
-    }
-}
-
-pub struct NetworkStatParser<'a> {
-    lines: std::str::Lines<'a>,
-}
-
-impl<'a> Iterator for NetworkStatParser<'a> {
-    type Item = Result<NetStats>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let line = loop {
-            let line = self.lines.next()?;
-            let trimmed = line.trim_start();
-            if trimmed.starts_with("en") || trimmed.starts_with("eth") || trimmed.starts_with("wlp")
-            {
-                break trimmed;
-            }
-        };
-
-        Some(NetworkSource::parse_line(line))
-    }
-}
@@ -0,0 +1,213 @@
+use crate::SensorData;
+use std::fmt::Write;
+use std::fs::{read_dir, read_to_string};
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct SwapDevice {
+    device: String,
+    kind: String,
+    size: u64,
+    used: u64,
+    priority: i32,
+}
+
+impl SensorData for SwapDevice {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"swap_size{{host="{}", device="{}", type="{}"}} {}"#,
+            hostname, self.device, self.kind, self.size
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"swap_used{{host="{}", device="{}", type="{}"}} {}"#,
+            hostname, self.device, self.kind, self.used
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"swap_priority{{host="{}", device="{}", type="{}"}} {}"#,
+            hostname, self.device, self.kind, self.priority
+        )
+        .ok();
+    }
+}
+
+pub fn swaps() -> Vec<SwapDevice> {
+    let content = read_to_string("/proc/swaps").unwrap_or_default();
+    content
+        .lines()
+        .skip(1)
+        .filter_map(parse_swap_line)
+        .collect()
+}
+
+/// Parse a line in the format `/dev/zram0    partition    8388604    0    100`, sizes are in KiB
+fn parse_swap_line(line: &str) -> Option<SwapDevice> {
+    let mut parts = line.split_ascii_whitespace();
+    let device = parts.next()?.to_string();
+    let kind = parts.next()?.to_string();
+    let size = parts.next()?.parse::<u64>().ok()? * 1024;
+    let used = parts.next()?.parse::<u64>().ok()? * 1024;
+    let priority = parts.next()?.parse().ok()?;
+    Some(SwapDevice {
+        device,
+        kind,
+        size,
+        used,
+        priority,
+    })
+}
+
+#[derive(Debug, Default)]
+pub struct ZramDevice {
+    name: String,
+    algorithm: String,
+    disk_size: u64,
+    original_size: u64,
+    compressed_size: u64,
+    memory_used: u64,
+    memory_limit: u64,
+    memory_used_max: u64,
+    same_pages: u64,
+    huge_pages: u64,
+}
+
+impl SensorData for ZramDevice {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"zram_info{{host="{}", device="{}", algorithm="{}"}} 1"#,
+            hostname, self.name, self.algorithm
+        )
+        .ok();
+        let values = [
+            ("disk_size", self.disk_size),
+            ("original_size", self.original_size),
+            ("compressed_size", self.compressed_size),
+            ("memory_used", self.memory_used),
+            ("memory_limit", self.memory_limit),
+            ("memory_used_max", self.memory_used_max),
+            ("same_pages", self.same_pages),
+            ("huge_pages", self.huge_pages),
+        ];
+        for (name, value) in values {
+            writeln!(
+                &mut w,
+                r#"zram_{}{{host="{}", device="{}"}} {}"#,
+                name, hostname, self.name, value
+            )
+            .ok();
+        }
+        if self.compressed_size > 0 {
+            writeln!(
+                &mut w,
+                r#"zram_compression_ratio{{host="{}", device="{}"}} {:.3}"#,
+                hostname,
+                self.name,
+                self.original_size as f64 / self.compressed_size as f64
+            )
+            .ok();
+        }
+    }
+}
+
+/// List all initialized zram devices
+pub fn zram() -> Vec<ZramDevice> {
+    read_dir("/sys/block")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("zram"))
+        .filter_map(|entry| zram_device(entry.file_name().to_string_lossy().into(), &entry.path()))
+        .collect()
+}
+
+fn zram_device(name: String, path: &Path) -> Option<ZramDevice> {
+    let disk_size = read_to_string(path.join("disksize"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    if disk_size == 0 {
+        return None;
+    }
+
+    // the active algorithm is marked with brackets: `lzo [lz4] zstd`
+    let algorithm = read_to_string(path.join("comp_algorithm"))
+        .unwrap_or_default()
+        .split_ascii_whitespace()
+        .find_map(|algorithm| algorithm.strip_prefix('[')?.strip_suffix(']'))
+        .unwrap_or_default()
+        .to_string();
+
+    let mm_stat = read_to_string(path.join("mm_stat")).ok()?;
+    let mut parts = mm_stat
+        .split_ascii_whitespace()
+        .map(|part| part.parse::<u64>().unwrap_or_default());
+
+    Some(ZramDevice {
+        name,
+        algorithm,
+        disk_size,
+        original_size: parts.next()?,
+        compressed_size: parts.next()?,
+        memory_used: parts.next()?,
+        memory_limit: parts.next()?,
+        memory_used_max: parts.next()?,
+        same_pages: parts.next()?,
+        // skip pages_compacted
+        huge_pages: parts.nth(1).unwrap_or_default(),
+    })
+}
+
+#[derive(Debug, Default)]
+pub struct Zswap {
+    enabled: bool,
+    compressor: String,
+    /// Statistics from debugfs, only available when debugfs is readable
+    stats: Vec<(String, u64)>,
+}
+
+impl SensorData for Zswap {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        writeln!(
+            &mut w,
+            r#"zswap_info{{host="{}", compressor="{}"}} 1"#,
+            hostname, self.compressor
+        )
+        .ok();
+        writeln!(
+            &mut w,
+            r#"zswap_enabled{{host="{}"}} {}"#,
+            hostname, self.enabled as u8
+        )
+        .ok();
+        for (name, value) in &self.stats {
+            writeln!(&mut w, r#"zswap_{}{{host="{}"}} {}"#, name, hostname, value).ok();
+        }
+    }
+}
+
+pub fn zswap() -> Option<Zswap> {
+    let enabled = read_to_string("/sys/module/zswap/parameters/enabled").ok()?;
+    let compressor = read_to_string("/sys/module/zswap/parameters/compressor").unwrap_or_default();
+
+    let stats = read_dir("/sys/kernel/debug/zswap")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let value = read_to_string(entry.path()).ok()?.trim().parse().ok()?;
+            Some((entry.file_name().into_string().ok()?, value))
+        })
+        .collect();
+
+    Some(Zswap {
+        enabled: enabled.trim() == "Y",
+        compressor: compressor.trim().into(),
+        stats,
+    })
+}
@@ -0,0 +1,184 @@
+use crate::{IoResultExt, Result, SensorData, SensorSource};
+use std::fmt::Write;
+use std::fs::{read_to_string, File};
+use std::io::{Read, Seek};
+
+/// Fields exported by default, `/proc/vmstat` contains a few hundred fields of which most are only useful for kernel debugging
+const DEFAULT_FIELDS: &[&str] = &[
+    "pgfault",
+    "pgmajfault",
+    // derived from pgfault - pgmajfault
+    "pgminfault",
+    "pgpgin",
+    "pgpgout",
+    "pswpin",
+    "pswpout",
+    "pgscan_kswapd",
+    "pgscan_direct",
+    "pgsteal_kswapd",
+    "pgsteal_direct",
+    "oom_kill",
+    "thp_fault_alloc",
+    "thp_fault_fallback",
+    "thp_collapse_alloc",
+    "thp_collapse_alloc_failed",
+    "thp_split_page",
+    "compact_stall",
+    "compact_fail",
+    "compact_success",
+];
+
+pub struct VmStatSource {
+    source: File,
+    buff: String,
+    fields: Vec<String>,
+}
+
+impl VmStatSource {
+    /// The exported fields can be configured with a comma separated list in `VMSTAT_FIELDS`
+    pub fn new() -> Result<VmStatSource> {
+        let fields = match dotenvy::var("VMSTAT_FIELDS") {
+            Ok(fields) => fields
+                .split(',')
+                .map(str::trim)
+                .filter(|field| !field.is_empty())
+                .map(String::from)
+                .collect(),
+            Err(_) => DEFAULT_FIELDS.iter().copied().map(String::from).collect(),
+        };
+        Ok(VmStatSource {
+            source: File::open("/proc/vmstat").context("error opening vmstat")?,
+            buff: String::new(),
+            fields,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct VmStat {
+    values: Vec<(String, u64)>,
+}
+
+impl SensorData for VmStat {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        for (name, value) in &self.values {
+            writeln!(&mut w, "vmstat_{}{{host=\"{}\"}} {}", name, hostname, value).ok();
+        }
+    }
+}
+
+impl SensorSource for VmStatSource {
+    type Data = VmStat;
+
+    fn read(&mut self) -> Result<Self::Data> {
+        self.buff.clear();
+        self.source.rewind().context("error rewinding vmstat")?;
+        self.source
+            .read_to_string(&mut self.buff)
+            .context("error reading vmstat")?;
+
+        Ok(parse_vmstat(&self.buff, &self.fields))
+    }
+}
+
+fn parse_vmstat(content: &str, fields: &[String]) -> VmStat {
+    let selected = |name: &str| fields.iter().any(|field| field == name);
+    let mut stats = VmStat::default();
+    let mut faults = None;
+    let mut major_faults = None;
+    for line in content.lines() {
+        let Some((name, value)) = line.split_once(' ') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<u64>() else {
+            continue;
+        };
+        match name {
+            "pgfault" => faults = Some(value),
+            "pgmajfault" => major_faults = Some(value),
+            _ => {}
+        }
+        if selected(name) {
+            stats.values.push((name.into(), value));
+        }
+    }
+
+    // pgfault counts both minor and major faults
+    if let (true, Some(faults), Some(major_faults)) = (selected("pgminfault"), faults, major_faults)
+    {
+        stats
+            .values
+            .push(("pgminfault".into(), faults.saturating_sub(major_faults)));
+    }
+
+    stats
+}
+
+#[derive(Debug)]
+pub struct BuddyZone {
+    node: String,
+    zone: String,
+    /// Number of free blocks for each order, a block of order `n` consists of `2^n` pages
+    free_blocks: Vec<u64>,
+}
+
+impl SensorData for BuddyZone {
+    fn write<W: Write>(&self, mut w: W, hostname: &str) {
+        for (order, free) in self.free_blocks.iter().enumerate() {
+            writeln!(
+                &mut w,
+                r#"buddyinfo_free_blocks{{host="{}", node="{}", zone="{}", order="{}"}} {}"#,
+                hostname, self.node, self.zone, order, free
+            )
+            .ok();
+        }
+    }
+}
+
+pub fn buddyinfo() -> Vec<BuddyZone> {
+    let content = read_to_string("/proc/buddyinfo").unwrap_or_default();
+    content.lines().filter_map(parse_buddyinfo_line).collect()
+}
+
+/// Parse a line in the format `Node 0, zone   Normal    182   1018    134 ...`
+fn parse_buddyinfo_line(line: &str) -> Option<BuddyZone> {
+    let (node, rest) = line.strip_prefix("Node ")?.split_once(',')?;
+    let mut parts = rest.split_ascii_whitespace();
+    if parts.next()? != "zone" {
+        return None;
+    }
+    let zone = parts.next()?.to_string();
+    let free_blocks = parts.map(str::parse).collect::<Result<_, _>>().ok()?;
+    Some(BuddyZone {
+        node: node.to_string(),
+        zone,
+        free_blocks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VMSTAT: &str =
+        "nr_free_pages 1024\npgfault 1000\npgmajfault 10\noom_kill invalid\npswpin 5\n";
+
+    fn fields(fields: &[&str]) -> Vec<String> {
+        fields.iter().copied().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_vmstat() {
+        let stats = parse_vmstat(VMSTAT, &fields(&["pgfault", "pgminfault", "oom_kill"]));
+        assert_eq!(
+            stats.values,
+            vec![("pgfault".into(), 1000), ("pgminfault".into(), 990)]
+        );
+    }
+
+    #[test]
+    fn test_minor_faults_not_selected() {
+        let stats = parse_vmstat(VMSTAT, &fields(&["pswpin"]));
+        assert_eq!(stats.values, vec![("pswpin".into(), 5)]);
+    }
+}
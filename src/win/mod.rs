@@ -63,13 +63,16 @@ pub fn get_metrics(sensors: &Sensors) -> Result<String> {
         total: system.total_memory(),
         available: system.available_memory(),
         free: system.free_memory(),
+        ..Default::default()
     };
     memory.write(&mut result, hostname);
     for disk in disks.iter() {
         let space = DiskUsage {
             name: disk.name().to_string_lossy().into(),
+            fstype: disk.file_system().to_string_lossy().into(),
             size: disk.total_space(),
             free: disk.available_space(),
+            ..Default::default()
         };
         space.write(&mut result, hostname);
     }
@@ -78,6 +81,11 @@ pub fn get_metrics(sensors: &Sensors) -> Result<String> {
             interface: interface.into(),
             bytes_received: net.total_received(),
             bytes_sent: net.total_transmitted(),
+            packets_received: net.total_packets_received(),
+            packets_sent: net.total_packets_transmitted(),
+            errors_received: net.total_errors_on_received(),
+            errors_sent: net.total_errors_on_transmitted(),
+            ..Default::default()
         };
         usage.write(&mut result, hostname);
     }
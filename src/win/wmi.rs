@@ -81,6 +81,7 @@ impl WmiSensor {
                     interface: "Total".to_string(),
                     bytes_sent: result.written,
                     bytes_received: result.read,
+                    ..Default::default()
                 }));
             }
         }